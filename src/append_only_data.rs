@@ -61,10 +61,11 @@
 // https://github.com/rust-lang/rust-clippy/issues/4326.
 #![allow(clippy::type_repetition_in_bounds)]
 
-use crate::{utils, Error, PublicKey, Result, XorName};
+use crate::{utils, Error, PublicKey, Result, XorName, MAX_PAGE_LIMIT};
 use multibase::Decodable;
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp,
     collections::{BTreeMap, BTreeSet},
     fmt::{self, Debug, Formatter},
     hash::Hash,
@@ -117,6 +118,40 @@ impl From<u64> for Index {
     }
 }
 
+/// Position in the entries of AppendOnlyData from which to get a page of entries.
+#[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct Cursor {
+    /// Index at which the page starts.
+    pub start: Index,
+    /// Maximum number of entries in the page. Must be non-zero, and is clamped to
+    /// [`MAX_PAGE_LIMIT`](constant.MAX_PAGE_LIMIT.html).
+    pub limit: u64,
+}
+
+/// A page of AppendOnlyData entries.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+pub struct Page {
+    /// Entries in the page.
+    pub entries: Entries,
+    /// Absolute index at which the next page starts, or `None` if this is the last page.
+    pub next: Option<u64>,
+}
+
+impl Page {
+    /// Returns true if there are no more pages after this one.
+    pub fn is_last(&self) -> bool {
+        self.next.is_none()
+    }
+
+    /// Returns the cursor for the next page, holding at most `limit` entries.
+    pub fn next_cursor(&self, limit: u64) -> Option<Cursor> {
+        self.next.map(|start| Cursor {
+            start: Index::FromStart(start),
+            limit,
+        })
+    }
+}
+
 /// Set of data, owners, permissions indices.
 #[derive(Copy, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct Indices {
@@ -407,6 +442,12 @@ pub trait AppendOnlyData<P> {
     /// Gets a list of keys and values with the given indices.
     fn in_range(&self, start: Index, end: Index) -> Option<Entries>;
 
+    /// Gets the page of entries at `cursor`.
+    ///
+    /// Returns `Err(Error::NoSuchEntry)` if the cursor's start index is out of bounds, or
+    /// `Err(Error::InvalidOperation)` if its limit is zero.
+    fn page(&self, cursor: Cursor) -> Result<Page>;
+
     /// Returns all entries.
     fn entries(&self) -> &Entries;
 
@@ -596,6 +637,24 @@ macro_rules! impl_appendable_data {
                 Some(self.inner.data[range].to_vec())
             }
 
+            fn page(&self, cursor: Cursor) -> Result<Page> {
+                // A zero limit would give a page which continues where it started.
+                if cursor.limit == 0 {
+                    return Err(Error::InvalidOperation);
+                }
+                let count = self.inner.data.len();
+                let start = to_absolute_index(cursor.start, count).ok_or(Error::NoSuchEntry)?;
+                let end = (start as u64)
+                    .saturating_add(cmp::min(cursor.limit, MAX_PAGE_LIMIT))
+                    .min(count as u64) as usize;
+                let next = if end < count { Some(end as u64) } else { None };
+
+                Ok(Page {
+                    entries: self.inner.data[start..end].to_vec(),
+                    next,
+                })
+            }
+
             /// Returns a complete list of entries.
             fn entries(&self) -> &Entries {
                 &self.inner.data
@@ -1100,6 +1159,16 @@ impl Data {
         }
     }
 
+    /// Gets the page of entries at `cursor`.
+    pub fn page(&self, cursor: Cursor) -> Result<Page> {
+        match self {
+            Data::PubSeq(data) => data.page(cursor),
+            Data::PubUnseq(data) => data.page(cursor),
+            Data::UnpubSeq(data) => data.page(cursor),
+            Data::UnpubUnseq(data) => data.page(cursor),
+        }
+    }
+
     /// Returns a value for the given key, if present.
     pub fn get(&self, key: &[u8]) -> Option<&Vec<u8>> {
         match self {
@@ -1123,6 +1192,14 @@ impl Data {
         }
     }
 
+    /// Returns true if any of the entries, owners or permissions indices of this data is greater
+    /// than in `known_indices`, i.e. if a copy of it at `known_indices` is stale.
+    pub fn is_newer_than(&self, known_indices: &Indices) -> bool {
+        self.entries_index() > known_indices.entries_index()
+            || self.owners_index() > known_indices.owners_index()
+            || self.permissions_index() > known_indices.permissions_index()
+    }
+
     /// Returns the last entry, if present.
     pub fn last_entry(&self) -> Option<&Entry> {
         match self {
@@ -1488,7 +1565,7 @@ mod tests {
         let name = XorName(rand::random());
         let address = Address::UnpubSeq { name, tag: 15000 };
         let encoded = address.encode_to_zbase32();
-        let decoded = unwrap!(self::Address::decode_from_zbase32(&encoded));
+        let decoded = unwrap!(Address::decode_from_zbase32(&encoded));
         assert_eq!(address, decoded);
     }
 
@@ -1618,6 +1695,101 @@ mod tests {
         assert_eq!(data.in_range(Index::FromEnd(3), Index::FromEnd(0)), None);
     }
 
+    #[test]
+    fn is_newer_than() {
+        let mut data = Data::from(PubSeqData::new(rand::random(), 10));
+        let known_indices = Indices::new(0, 0, 0);
+        assert!(!data.is_newer_than(&known_indices));
+
+        let owner = Owner {
+            public_key: SecretKey::random().public_key().into(),
+            entries_index: 0,
+            permissions_index: 0,
+        };
+        unwrap!(data.append_owner(owner, 0));
+        assert!(data.is_newer_than(&known_indices));
+        assert!(!data.is_newer_than(&unwrap!(data.indices())));
+
+        let entries = vec![Entry::new(b"key".to_vec(), b"value".to_vec())];
+        unwrap!(data.append_seq(entries, 0));
+        assert!(data.is_newer_than(&Indices::new(0, 1, 0)));
+        assert!(!data.is_newer_than(&Indices::new(1, 1, 0)));
+    }
+
+    #[test]
+    fn page() {
+        let mut data = PubSeqData::new(rand::random(), 10);
+        let entries: Entries = (0..5u8).map(|i| Entry::new(vec![i], vec![i])).collect();
+        unwrap!(data.append(entries.clone(), 0));
+
+        // Page through all the entries, two at a time.
+        let mut cursor = Cursor {
+            start: Index::FromStart(0),
+            limit: 2,
+        };
+        let mut pages = Vec::new();
+        loop {
+            let page = unwrap!(data.page(cursor));
+            pages.push(page.entries.clone());
+            match page.next_cursor(2) {
+                Some(next) => cursor = next,
+                None => break,
+            }
+        }
+        assert_eq!(
+            pages.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert_eq!(pages.concat(), entries);
+
+        // Relative start index.
+        let page = unwrap!(data.page(Cursor {
+            start: Index::FromEnd(2),
+            limit: 10,
+        }));
+        assert_eq!(page.entries, entries[3..].to_vec());
+        assert!(page.is_last());
+
+        // Starting at the end gives an empty last page.
+        let page = unwrap!(data.page(Cursor {
+            start: Index::FromEnd(0),
+            limit: 10,
+        }));
+        assert!(page.entries.is_empty());
+        assert!(page.is_last());
+
+        // Out of bounds.
+        assert_eq!(
+            data.page(Cursor {
+                start: Index::FromStart(6),
+                limit: 10,
+            }),
+            Err(Error::NoSuchEntry)
+        );
+
+        // A zero limit would never get past the start.
+        assert_eq!(
+            data.page(Cursor {
+                start: Index::FromStart(0),
+                limit: 0,
+            }),
+            Err(Error::InvalidOperation)
+        );
+
+        // Limits are clamped to the maximum page size.
+        let mut data = PubSeqData::new(rand::random(), 10);
+        let entries: Entries = (0..=MAX_PAGE_LIMIT)
+            .map(|i| Entry::new(i.to_be_bytes().to_vec(), Vec::new()))
+            .collect();
+        unwrap!(data.append(entries, 0));
+        let page = unwrap!(data.page(Cursor {
+            start: Index::FromStart(0),
+            limit: u64::MAX,
+        }));
+        assert_eq!(page.entries.len() as u64, MAX_PAGE_LIMIT);
+        assert_eq!(page.next, Some(MAX_PAGE_LIMIT));
+    }
+
     #[test]
     fn get_permissions() {
         let public_key = gen_public_key();
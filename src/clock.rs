@@ -0,0 +1,51 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use std::time::Instant;
+
+/// Source of the current time for time-aware structures.
+///
+/// This allows tests to control the passage of time instead of relying on the system clock.
+pub trait Clock {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+}
+
+/// `Clock` backed by the system's monotonic clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// `Clock` whose time only moves when told to, for use in tests.
+#[cfg(test)]
+#[derive(Clone)]
+pub(crate) struct MockClock(std::rc::Rc<std::cell::Cell<Instant>>);
+
+#[cfg(test)]
+impl MockClock {
+    pub fn new() -> Self {
+        MockClock(std::rc::Rc::new(std::cell::Cell::new(Instant::now())))
+    }
+
+    pub fn advance(&self, duration: std::time::Duration) {
+        self.0.set(self.0.get() + duration);
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.0.get()
+    }
+}
@@ -46,6 +46,28 @@ impl Coins {
     }
 }
 
+/// Prices charged for requests which store data or transfer coins, used by `Request::cost`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, Debug)]
+pub struct PricingPolicy {
+    /// Flat price of each mutation or transaction.
+    pub per_request: Coins,
+    /// Price of each byte stored by a mutation or transaction.
+    pub per_byte: Coins,
+}
+
+impl PricingPolicy {
+    /// Returns the price of a mutation or transaction storing `bytes` bytes, saturating at the
+    /// largest representable amount rather than overflowing.
+    pub fn price(&self, bytes: u64) -> Coins {
+        Coins::from_nano(
+            self.per_byte
+                .as_nano()
+                .saturating_mul(bytes)
+                .saturating_add(self.per_request.as_nano()),
+        )
+    }
+}
+
 impl FromStr for Coins {
     type Err = Error;
 
@@ -59,7 +81,7 @@ impl FromStr for Coins {
 
             units
                 .checked_mul(COIN_TO_RAW_CONVERSION)
-                .ok_or_else(|| Error::ExcessiveValue)?
+                .ok_or(Error::ExcessiveValue)?
         };
 
         let remainder = {
@@ -74,7 +96,7 @@ impl FromStr for Coins {
 
                 let remainder_conversion = COIN_TO_RAW_POWER_OF_10_CONVERSION
                     .checked_sub(remainder_str.len() as u32)
-                    .ok_or_else(|| Error::LossOfPrecision)?;
+                    .ok_or(Error::LossOfPrecision)?;
                 parsed_remainder * 10_u64.pow(remainder_conversion)
             }
         };
@@ -93,14 +115,13 @@ impl Display for Coins {
     fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
         let unit = self.0 / COIN_TO_RAW_CONVERSION;
         let remainder = self.0 % COIN_TO_RAW_CONVERSION;
-        write!(formatter, "{}.{}", unit, format!("{:09}", remainder))
+        write!(formatter, "{}.{:09}", unit, remainder)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::u64;
     use unwrap::unwrap;
 
     #[test]
@@ -177,4 +198,15 @@ mod tests {
         assert_eq!(None, Coins(0).checked_sub(Coins(u64::MAX)));
         assert_eq!(None, Coins(10).checked_sub(Coins(11)));
     }
+
+    #[test]
+    fn price() {
+        let policy = PricingPolicy {
+            per_request: Coins(10),
+            per_byte: Coins(3),
+        };
+        assert_eq!(policy.price(0), Coins(10));
+        assert_eq!(policy.price(5), Coins(25));
+        assert_eq!(policy.price(u64::MAX), Coins(u64::MAX));
+    }
 }
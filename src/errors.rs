@@ -96,6 +96,22 @@ pub enum Error {
     BalanceExists,
     /// Expected data size exceeded.
     ExceededSize,
+    /// The message was encoded with an unsupported wire protocol version. Contains that version.
+    UnsupportedProtocolVersion(u16),
+    /// Received a response whose `MessageId` doesn't match any pending request.
+    NoSuchPendingRequest,
+    /// Received a response of a type which doesn't match the pending request.
+    UnexpectedResponse,
+    /// No response was received for the request in time.
+    RequestTimedOut,
+    /// The request wasn't applied because another request in the same atomic batch failed.
+    BatchAborted,
+    /// A chunk doesn't match its entry in the data map.
+    InvalidChunk,
+    /// Data received doesn't match the address it was requested from.
+    IntegrityCheckFailed,
+    /// The requested revision of an entry is older than the history kept for it.
+    HistoryNotKept,
 }
 
 impl<T: Into<String>> From<T> for Error {
@@ -154,6 +170,18 @@ impl Display for Error {
             Error::BalanceExists => write!(f, "Balance already exists"),
             Error::DuplicateMessageId => write!(f, "MessageId already exists"),
             Error::ExceededSize => write!(f, "Size of the structure exceeds the limit"),
+            Error::UnsupportedProtocolVersion(version) => {
+                write!(f, "Unsupported wire protocol version: {}", version)
+            }
+            Error::NoSuchPendingRequest => write!(f, "No pending request with this MessageId"),
+            Error::UnexpectedResponse => write!(f, "Response type doesn't match the request"),
+            Error::RequestTimedOut => write!(f, "Timed out waiting for the response"),
+            Error::BatchAborted => write!(f, "Another request in the atomic batch failed"),
+            Error::InvalidChunk => write!(f, "Chunk does not match the data map"),
+            Error::IntegrityCheckFailed => {
+                write!(f, "Data does not match the address it was requested from")
+            }
+            Error::HistoryNotKept => write!(f, "History of the entry at this version is not kept"),
         }
     }
 }
@@ -191,6 +219,14 @@ impl error::Error for Error {
             Error::BalanceExists => "Balance already exists",
             Error::DuplicateMessageId => "MessageId already exists",
             Error::ExceededSize => "Exceeded the size limit",
+            Error::UnsupportedProtocolVersion(_) => "Unsupported wire protocol version",
+            Error::NoSuchPendingRequest => "No such pending request",
+            Error::UnexpectedResponse => "Unexpected response type",
+            Error::RequestTimedOut => "Request timed out",
+            Error::BatchAborted => "Atomic batch aborted",
+            Error::InvalidChunk => "Invalid chunk",
+            Error::IntegrityCheckFailed => "Integrity check failed",
+            Error::HistoryNotKept => "History not kept",
         }
     }
 }
@@ -204,4 +240,9 @@ pub enum EntryError {
     EntryExists(u8),
     /// Invalid version when updating an entry. Contains the current entry Key.
     InvalidSuccessor(u8),
+    /// The entry's current value doesn't equal the one a conditional action expected.
+    ValueMismatch,
+    /// The entry was deleted, and can only be re-inserted with the next version. Contains the
+    /// version of the deletion.
+    Deleted(u64),
 }
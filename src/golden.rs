@@ -0,0 +1,669 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Serialisation compatibility tests.
+//!
+//! Vaults and clients must agree byte-for-byte on the serialised form of the types exchanged over
+//! the network. The fixtures below are generated deterministically from a seeded RNG and their
+//! bincode encodings are compared against the golden vectors checked in under `golden/`. Any
+//! change to the encoding of these types makes these tests fail.
+//!
+//! If a change to the wire format is intended, bump `PROTOCOL_VERSION` and regenerate the vectors
+//! by running the tests with `UPDATE_GOLDEN_VECTORS=1`.
+
+use crate::{
+    utils, AData, ADataAddress, ADataAppendOperation, ADataCursor, ADataEntry, ADataIndex,
+    ADataIndices, ADataOwner, ADataPage, ADataPubPermissionSet, ADataPubPermissions,
+    ADataUnpubPermissionSet, ADataUnpubPermissions, ADataUser, AppPermissions, AppendOnlyData,
+    Approvals, BatchMode, ClientFullId, Coins, DataChange, Error, HandshakeRequest, IData,
+    IDataAddress, IDataRetention, IfModified, Keypair, MData, MDataAction, MDataAddress,
+    MDataCursor, MDataPage, MDataPermissionSet, MDataRevision, MDataSeqEntryActions, MDataSeqValue,
+    MDataUnseqEntryActions, Message, MessageId, NodeFullId, Notification, Owners, PubImmutableData,
+    PubSeqAppendOnlyData, PublicId, PublicKey, Request, Response, RetainedUnpubImmutableData,
+    SeqAppendOnly, SeqMutableData, Signature, Signer, SubscriptionTarget, Transaction,
+    UnpubImmutableData, UnpubUnseqAppendOnlyData, UnseqMutableData, XorName,
+};
+use rand::{CryptoRng, Error as RandError, Rng, RngCore, SeedableRng};
+use rand_xorshift::XorShiftRng;
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    fmt::Debug,
+    fs, iter,
+    path::PathBuf,
+};
+use unwrap::unwrap;
+
+const SEED: u64 = 0x5afe_0d47_a000_0001;
+const UPDATE_ENV_VAR: &str = "UPDATE_GOLDEN_VECTORS";
+
+/// Seeded RNG used to generate the fixtures.
+///
+/// It's marked as a `CryptoRng` only so that it can be used to generate keypairs deterministically.
+/// It must never be used outside of tests.
+struct FixtureRng(XorShiftRng);
+
+impl FixtureRng {
+    fn new() -> Self {
+        FixtureRng(XorShiftRng::seed_from_u64(SEED))
+    }
+}
+
+impl RngCore for FixtureRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), RandError> {
+        self.0.try_fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for FixtureRng {}
+
+/// Deterministic fixtures shared by all the golden vector tests.
+struct Fixtures {
+    rng: FixtureRng,
+    ed25519: Keypair,
+    bls: Keypair,
+    bls_share: Keypair,
+}
+
+impl Fixtures {
+    fn new() -> Self {
+        let mut rng = FixtureRng::new();
+        let ed25519 = Keypair::new_ed25519(&mut rng);
+        let bls = Keypair::new_bls(&mut rng);
+        let bls_share = Keypair::new_bls_share(
+            threshold_crypto::SecretKeySet::random(1, &mut rng).secret_key_share(0),
+        );
+        Self {
+            rng,
+            ed25519,
+            bls,
+            bls_share,
+        }
+    }
+
+    fn name(&mut self) -> XorName {
+        self.rng.gen()
+    }
+
+    fn public_keys(&self) -> Vec<(&'static str, PublicKey)> {
+        vec![
+            ("ed25519", self.ed25519.public_key()),
+            ("bls", self.bls.public_key()),
+            ("bls_share", self.bls_share.public_key()),
+        ]
+    }
+
+    fn signatures(&self) -> Vec<(&'static str, Signature)> {
+        let data = b"golden";
+        vec![
+            ("ed25519", self.ed25519.sign(data)),
+            ("bls", self.bls.sign(data)),
+            ("bls_share", self.bls_share.sign(data)),
+        ]
+    }
+
+    fn coins(&self) -> Vec<(&'static str, Coins)> {
+        vec![
+            ("zero", Coins::from_nano(0)),
+            ("one_and_a_bit", Coins::from_nano(1_000_000_001)),
+            ("max", Coins::from_nano(u64::MAX)),
+        ]
+    }
+
+    fn idata(&mut self) -> Vec<(&'static str, IData)> {
+        vec![
+            (
+                "pub",
+                PubImmutableData::new(b"published chunk".to_vec()).into(),
+            ),
+            (
+                "unpub",
+                UnpubImmutableData::new(b"unpublished chunk".to_vec(), self.bls.public_key())
+                    .into(),
+            ),
+            (
+                "retained",
+                RetainedUnpubImmutableData::new(
+                    UnpubImmutableData::new(b"retained chunk".to_vec(), self.bls.public_key()),
+                    IDataRetention::RefCount(2),
+                )
+                .into(),
+            ),
+        ]
+    }
+
+    fn mdata(&mut self) -> Vec<(&'static str, MData)> {
+        let owner = self.ed25519.public_key();
+        let mut permissions = BTreeMap::new();
+        let _ = permissions.insert(
+            self.bls.public_key(),
+            MDataPermissionSet::new()
+                .allow(MDataAction::Read)
+                .allow(MDataAction::Insert),
+        );
+
+        let mut seq_entries = BTreeMap::new();
+        let _ = seq_entries.insert(
+            b"seq key".to_vec(),
+            MDataSeqValue {
+                data: b"seq value".to_vec(),
+                version: 3,
+            },
+        );
+        let mut seq = SeqMutableData::new_with_data(
+            self.name(),
+            15000,
+            seq_entries,
+            permissions.clone(),
+            owner,
+        );
+        unwrap!(seq.change_owner(self.bls.public_key(), 1));
+
+        let mut unseq_entries = BTreeMap::new();
+        let _ = unseq_entries.insert(b"unseq key".to_vec(), b"unseq value".to_vec());
+        let unseq =
+            UnseqMutableData::new_with_data(self.name(), 15001, unseq_entries, permissions, owner);
+
+        let mut owner_keys = BTreeSet::new();
+        let _ = owner_keys.insert(owner);
+        let _ = owner_keys.insert(self.bls.public_key());
+        let threshold_owned = UnseqMutableData::new(
+            *unseq.name(),
+            15002,
+            unwrap!(Owners::threshold(owner_keys, 2)),
+        );
+
+        vec![
+            ("seq", seq.into()),
+            ("unseq", unseq.into()),
+            ("unseq_threshold_owners", threshold_owned.into()),
+        ]
+    }
+
+    fn adata(&mut self) -> Vec<(&'static str, AData)> {
+        let owner = ADataOwner {
+            public_key: self.ed25519.public_key(),
+            entries_index: 0,
+            permissions_index: 0,
+        };
+
+        let mut pub_seq = PubSeqAppendOnlyData::new(self.name(), 10000);
+        unwrap!(pub_seq.append_owner(owner, 0));
+        let mut pub_permissions = ADataPubPermissions {
+            permissions: BTreeMap::new(),
+            entries_index: 0,
+            owners_index: 1,
+        };
+        let _ = pub_permissions
+            .permissions
+            .insert(ADataUser::Anyone, ADataPubPermissionSet::new(true, false));
+        unwrap!(pub_seq.append_permissions(pub_permissions, 0));
+        unwrap!(pub_seq.append(vec![ADataEntry::new(b"key".to_vec(), b"value".to_vec())], 0));
+
+        let mut unpub_unseq = UnpubUnseqAppendOnlyData::new(self.name(), 10001);
+        unwrap!(unpub_unseq.append_owner(owner, 0));
+        let mut unpub_permissions = ADataUnpubPermissions {
+            permissions: BTreeMap::new(),
+            entries_index: 0,
+            owners_index: 1,
+        };
+        let _ = unpub_permissions.permissions.insert(
+            self.bls.public_key(),
+            ADataUnpubPermissionSet::new(true, true, false),
+        );
+        unwrap!(unpub_unseq.append_permissions(unpub_permissions, 0));
+
+        vec![
+            ("pub_seq", pub_seq.into()),
+            ("unpub_unseq", unpub_unseq.into()),
+        ]
+    }
+
+    fn requests(&mut self) -> Vec<(&'static str, Request)> {
+        let idata = self.idata().remove(0).1;
+        let mdata_address = MDataAddress::Seq {
+            name: self.name(),
+            tag: 15000,
+        };
+        let adata_address = ADataAddress::PubSeq {
+            name: self.name(),
+            tag: 10000,
+        };
+
+        vec![
+            ("put_idata", Request::PutIData(idata)),
+            (
+                "get_idata",
+                Request::GetIData(IDataAddress::Unpub(self.name())),
+            ),
+            ("get_mdata_shell", Request::GetMDataShell(mdata_address)),
+            (
+                "mutate_seq_mdata_entries",
+                Request::MutateMDataEntries {
+                    address: mdata_address,
+                    actions: MDataSeqEntryActions::new()
+                        .ins(b"new".to_vec(), b"value".to_vec(), 0)
+                        .update(b"old".to_vec(), b"value".to_vec(), 2)
+                        .del(b"gone".to_vec(), 5)
+                        .into(),
+                },
+            ),
+            (
+                "mutate_unseq_mdata_entries",
+                Request::MutateMDataEntries {
+                    address: MDataAddress::Unseq {
+                        name: self.name(),
+                        tag: 15001,
+                    },
+                    actions: MDataUnseqEntryActions::new()
+                        .ins(b"new".to_vec(), b"value".to_vec())
+                        .del(b"gone".to_vec())
+                        .into(),
+                },
+            ),
+            (
+                "set_mdata_user_permissions",
+                Request::SetMDataUserPermissions {
+                    address: mdata_address,
+                    user: self.bls.public_key(),
+                    permissions: MDataPermissionSet::new().allow(MDataAction::Update),
+                    version: 4,
+                },
+            ),
+            (
+                "get_adata_range",
+                Request::GetADataRange {
+                    address: adata_address,
+                    range: (ADataIndex::FromStart(1), ADataIndex::FromEnd(0)),
+                },
+            ),
+            (
+                "append_seq",
+                Request::AppendSeq {
+                    append: ADataAppendOperation {
+                        address: adata_address,
+                        values: vec![ADataEntry::new(b"key".to_vec(), b"value".to_vec())],
+                    },
+                    index: 1,
+                },
+            ),
+            (
+                "transfer_coins",
+                Request::TransferCoins {
+                    destination: self.name(),
+                    amount: Coins::from_nano(123_456_789),
+                    transaction_id: 42,
+                },
+            ),
+            ("get_balance", Request::GetBalance),
+            (
+                "ins_auth_key",
+                Request::InsAuthKey {
+                    key: self.ed25519.public_key(),
+                    version: 7,
+                    permissions: AppPermissions {
+                        transfer_coins: true,
+                        perform_mutations: false,
+                        get_balance: true,
+                    },
+                },
+            ),
+            (
+                "list_mdata_entries_page",
+                Request::ListMDataEntriesPage {
+                    address: mdata_address,
+                    cursor: MDataCursor {
+                        start: Some(b"key".to_vec()),
+                        limit: 100,
+                    },
+                },
+            ),
+            (
+                "get_adata_range_page",
+                Request::GetADataRangePage {
+                    address: adata_address,
+                    cursor: ADataCursor {
+                        start: ADataIndex::FromEnd(10),
+                        limit: 5,
+                    },
+                },
+            ),
+            (
+                "batch",
+                Request::Batch {
+                    requests: vec![
+                        Request::GetMDataShell(mdata_address),
+                        Request::ListMDataPermissions(mdata_address),
+                        Request::GetMDataVersion(mdata_address),
+                    ],
+                    mode: BatchMode::Atomic,
+                },
+            ),
+            (
+                "get_adata_if_newer",
+                Request::GetADataIfNewer {
+                    address: adata_address,
+                    known_indices: ADataIndices::new(3, 1, 2),
+                },
+            ),
+            (
+                "subscribe",
+                Request::Subscribe(SubscriptionTarget::AData(adata_address)),
+            ),
+            (
+                "get_mdata_value_at",
+                Request::GetMDataValueAt {
+                    address: mdata_address,
+                    key: b"seq key".to_vec(),
+                    version: 2,
+                },
+            ),
+            (
+                "mutate_unseq_mdata_entries_conditional",
+                Request::MutateMDataEntries {
+                    address: MDataAddress::Unseq {
+                        name: *mdata_address.name(),
+                        tag: 15001,
+                    },
+                    actions: MDataUnseqEntryActions::new()
+                        .update_if_equals(b"key".to_vec(), b"old".to_vec(), b"new".to_vec())
+                        .del_if_equals(b"gone".to_vec(), b"value".to_vec())
+                        .upsert(b"new".to_vec(), b"value".to_vec())
+                        .into(),
+                },
+            ),
+        ]
+    }
+
+    fn responses(&mut self) -> Vec<(&'static str, Response)> {
+        let idata = self.idata().remove(1).1;
+        let mdata = self.mdata().remove(0).1;
+        let adata = self.adata().remove(0).1;
+
+        vec![
+            ("get_idata", Response::GetIData(Ok(idata))),
+            ("get_mdata", Response::GetMData(Ok(mdata.clone()))),
+            (
+                "list_mdata_permissions",
+                Response::ListMDataPermissions(Ok(mdata.permissions())),
+            ),
+            ("get_adata", Response::GetAData(Ok(adata))),
+            ("get_balance", Response::GetBalance(Ok(Coins::from_nano(9)))),
+            (
+                "transaction",
+                Response::Transaction(Ok(Transaction {
+                    id: 42,
+                    amount: Coins::from_nano(123_456_789),
+                })),
+            ),
+            ("mutation_ok", Response::Mutation(Ok(()))),
+            ("mutation_err", Response::Mutation(Err(Error::AccessDenied))),
+            (
+                "invalid_successor",
+                Response::GetMDataVersion(Err(Error::InvalidSuccessor(3))),
+            ),
+            (
+                "list_mdata_keys_page",
+                Response::ListMDataKeysPage(Ok(MDataPage {
+                    items: mdata.keys(),
+                    next: Some(b"next".to_vec()),
+                })),
+            ),
+            (
+                "get_adata_range_page",
+                Response::GetADataRangePage(Ok(ADataPage {
+                    entries: vec![ADataEntry::new(b"key".to_vec(), b"value".to_vec())],
+                    next: None,
+                })),
+            ),
+            (
+                "batch",
+                Response::Batch(vec![
+                    Response::GetMDataVersion(Ok(3)),
+                    Response::Mutation(Err(Error::BatchAborted)),
+                ]),
+            ),
+            (
+                "get_mdata_if_newer_not_modified",
+                Response::GetMDataIfNewer(Ok(IfModified::NotModified)),
+            ),
+            (
+                "get_mdata_entry_history",
+                Response::GetMDataEntryHistory(Ok(vec![
+                    MDataRevision::Value(MDataSeqValue {
+                        data: b"seq value".to_vec(),
+                        version: 3,
+                    }),
+                    MDataRevision::Deleted(4),
+                ])),
+            ),
+        ]
+    }
+
+    fn messages(&mut self) -> Vec<(&'static str, Message)> {
+        let client_id = ClientFullId::new_ed25519(&mut self.rng);
+        let request = self.requests().remove(3).1;
+        let message_id = MessageId(self.name());
+        let signature = client_id.sign(utils::serialise(&(&request, message_id)));
+
+        vec![
+            (
+                "request",
+                Message::Request {
+                    request: request.clone(),
+                    message_id,
+                    signature: Some(signature.clone()),
+                    approvals: None,
+                },
+            ),
+            (
+                "response",
+                Message::Response {
+                    response: Response::Mutation(Ok(())),
+                    message_id: MessageId(self.name()),
+                },
+            ),
+            (
+                "notification",
+                Message::Notification {
+                    notification: Notification::Transaction(Transaction {
+                        id: 42,
+                        amount: Coins::from_nano(1),
+                    }),
+                },
+            ),
+            (
+                "mdata_notification",
+                Message::Notification {
+                    notification: Notification::MDataChanged {
+                        address: MDataAddress::Seq {
+                            name: self.name(),
+                            tag: 15000,
+                        },
+                        change: DataChange::Permissions,
+                        version: 5,
+                        mutator: PublicId::Client(client_id.public_id().clone()),
+                    },
+                },
+            ),
+            (
+                "approved_request",
+                Message::Request {
+                    request,
+                    message_id,
+                    signature: Some(signature.clone()),
+                    approvals: Some(Approvals::Signatures(
+                        iter::once((client_id.public_key(), signature)).collect(),
+                    )),
+                },
+            ),
+        ]
+    }
+
+    fn handshake_requests(&mut self) -> Vec<(&'static str, HandshakeRequest)> {
+        let client_id = ClientFullId::new_bls(&mut self.rng);
+        let node_id = NodeFullId::new(&mut self.rng);
+
+        vec![
+            (
+                "bootstrap",
+                HandshakeRequest::Bootstrap(PublicId::Client(client_id.public_id().clone())),
+            ),
+            (
+                "join",
+                HandshakeRequest::Join(PublicId::Node(node_id.public_id().clone())),
+            ),
+            (
+                "challenge_result",
+                HandshakeRequest::ChallengeResult(Signer::sign(&node_id, b"challenge")),
+            ),
+        ]
+    }
+}
+
+fn golden_path(type_name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("golden")
+        .join(format!("{}.hex", type_name))
+}
+
+/// Checks the serialised `cases` against the golden vectors for `type_name`, or rewrites the golden
+/// vectors if `UPDATE_GOLDEN_VECTORS` is set.
+fn check_golden<T: Serialize>(type_name: &str, cases: &[(&'static str, T)]) -> Vec<Vec<u8>> {
+    let path = golden_path(type_name);
+    let actual: Vec<(&str, String)> = cases
+        .iter()
+        .map(|(case, value)| (*case, hex::encode(utils::serialise(value))))
+        .collect();
+
+    if env::var(UPDATE_ENV_VAR).is_ok() {
+        let contents: String = actual
+            .iter()
+            .map(|(case, encoded)| format!("{} {}\n", case, encoded))
+            .collect();
+        unwrap!(fs::write(&path, contents));
+    }
+
+    let contents = unwrap!(
+        fs::read_to_string(&path),
+        "Missing golden vectors at {}; run with {}=1 to generate them.",
+        path.display(),
+        UPDATE_ENV_VAR
+    );
+    let expected: Vec<(&str, &str)> = contents
+        .lines()
+        .map(|line| {
+            let mut parts = line.splitn(2, ' ');
+            (unwrap!(parts.next()), unwrap!(parts.next()))
+        })
+        .collect();
+
+    assert_eq!(
+        expected.iter().map(|(case, _)| *case).collect::<Vec<_>>(),
+        actual.iter().map(|(case, _)| *case).collect::<Vec<_>>(),
+        "The set of golden vector cases for {} has changed.",
+        type_name
+    );
+    for ((case, expected), (_, actual)) in expected.iter().zip(actual.iter()) {
+        assert_eq!(
+            expected, actual,
+            "Serialisation of {} ({}) doesn't match its golden vector.",
+            type_name, case
+        );
+    }
+
+    expected
+        .iter()
+        .map(|(_, encoded)| unwrap!(hex::decode(encoded)))
+        .collect()
+}
+
+/// Checks the golden vectors and that they deserialise back into the fixtures.
+fn check_round_trip<T>(type_name: &str, cases: &[(&'static str, T)])
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    let golden = check_golden(type_name, cases);
+    for ((case, value), bytes) in cases.iter().zip(golden) {
+        let decoded: T = unwrap!(bincode::deserialize(&bytes));
+        assert_eq!(
+            &decoded, value,
+            "Golden vector of {} ({}) doesn't deserialise into the fixture.",
+            type_name, case
+        );
+    }
+}
+
+#[test]
+fn public_key() {
+    check_round_trip("public_key", &Fixtures::new().public_keys());
+}
+
+#[test]
+fn signature() {
+    check_round_trip("signature", &Fixtures::new().signatures());
+}
+
+#[test]
+fn coins() {
+    check_round_trip("coins", &Fixtures::new().coins());
+}
+
+#[test]
+fn idata() {
+    check_round_trip("idata", &Fixtures::new().idata());
+}
+
+#[test]
+fn mdata() {
+    check_round_trip("mdata", &Fixtures::new().mdata());
+}
+
+#[test]
+fn adata() {
+    check_round_trip("adata", &Fixtures::new().adata());
+}
+
+#[test]
+fn request() {
+    check_round_trip("request", &Fixtures::new().requests());
+}
+
+#[test]
+fn response() {
+    check_round_trip("response", &Fixtures::new().responses());
+}
+
+#[test]
+fn message() {
+    check_round_trip("message", &Fixtures::new().messages());
+}
+
+#[test]
+fn handshake_request() {
+    // `HandshakeRequest` doesn't implement `PartialEq`, so check that it re-serialises identically.
+    let cases = Fixtures::new().handshake_requests();
+    for bytes in check_golden("handshake_request", &cases) {
+        let decoded: HandshakeRequest = unwrap!(bincode::deserialize(&bytes));
+        assert_eq!(utils::serialise(&decoded), bytes);
+    }
+}
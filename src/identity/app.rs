@@ -8,8 +8,7 @@
 // Software.
 
 use crate::{
-    utils, ClientFullId, ClientPublicId, Ed25519Digest, Error, Keypair, PublicKey, Signature,
-    XorName,
+    utils, ClientFullId, ClientPublicId, Error, Keypair, PublicKey, Signature, Signer, XorName,
 };
 use multibase::Decodable;
 use rand::{CryptoRng, Rng};
@@ -53,11 +52,7 @@ impl FullId {
 
     /// Creates a detached signature of `data`.
     pub fn sign<T: AsRef<[u8]>>(&self, data: T) -> Signature {
-        match &self.keypair {
-            Keypair::Ed25519(keys) => Signature::Ed25519(keys.sign::<Ed25519Digest>(data.as_ref())),
-            Keypair::Bls(keys) => Signature::Bls(keys.secret.inner().sign(data)),
-            Keypair::BlsShare(keys) => Signature::BlsShare(keys.secret.inner().sign(data)),
-        }
+        self.keypair.sign(data)
     }
 
     /// Returns the public ID.
@@ -66,6 +61,16 @@ impl FullId {
     }
 }
 
+impl Signer for FullId {
+    fn sign(&self, data: &[u8]) -> Signature {
+        self.keypair.sign(data)
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.public_id.public_key
+    }
+}
+
 /// A struct representing the public identity of a network App.
 ///
 /// It includes the public signing key, and the App owner's `ClientPublicId`.  The owner's `name()`
@@ -8,7 +8,7 @@
 // Software.
 
 use crate::keys::BlsKeypair;
-use crate::{utils, Ed25519Digest, Error, Keypair, PublicKey, Signature, XorName};
+use crate::{utils, Error, Keypair, PublicKey, Signature, Signer, XorName};
 use ed25519_dalek::Keypair as Ed25519Keypair;
 use multibase::Decodable;
 use rand::{CryptoRng, Rng};
@@ -61,11 +61,7 @@ impl FullId {
 
     /// Creates a detached signature of `data`.
     pub fn sign<T: AsRef<[u8]>>(&self, data: T) -> Signature {
-        match &self.keypair {
-            Keypair::Ed25519(keys) => Signature::Ed25519(keys.sign::<Ed25519Digest>(data.as_ref())),
-            Keypair::Bls(keys) => Signature::Bls(keys.secret.inner().sign(data)),
-            Keypair::BlsShare(keys) => Signature::BlsShare(keys.secret.inner().sign(data)),
-        }
+        self.keypair.sign(data)
     }
 
     /// Returns the public ID.
@@ -74,6 +70,16 @@ impl FullId {
     }
 }
 
+impl Signer for FullId {
+    fn sign(&self, data: &[u8]) -> Signature {
+        self.keypair.sign(data)
+    }
+
+    fn public_key(&self) -> PublicKey {
+        self.public_id.public_key
+    }
+}
+
 impl From<BlsSecretKey> for FullId {
     fn from(bls_sk: BlsSecretKey) -> Self {
         let public = bls_sk.public_key();
@@ -143,7 +149,7 @@ impl PublicId {
 
 impl Serialize for PublicId {
     fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
-        (&self.public_key).serialize(serialiser)
+        self.public_key.serialize(serialiser)
     }
 }
 
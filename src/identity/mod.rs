@@ -105,27 +105,48 @@ impl Display for PublicId {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{ClientFullId, Error};
+    use crate::{ClientFullId, Error, Signer};
     use unwrap::unwrap;
 
+    fn check_signer<S: Signer>(signer: &S, public_id: PublicId) {
+        let data = b"some data";
+        let signature = signer.sign(data);
+        assert_eq!(signer.public_key(), public_id.public_key());
+        unwrap!(public_id.public_key().verify(&signature, data));
+    }
+
+    #[test]
+    fn full_ids_as_signers() {
+        let mut rng = rand::thread_rng();
+
+        let client_id = client::FullId::new_bls(&mut rng);
+        check_signer(&client_id, PublicId::Client(client_id.public_id().clone()));
+
+        let app_id = app::FullId::new_ed25519(&mut rng, client_id.public_id().clone());
+        check_signer(&app_id, PublicId::App(app_id.public_id().clone()));
+
+        let mut node_id = node::FullId::new(&mut rng);
+        let bls_secret_key = threshold_crypto::SecretKeySet::random(1, &mut rng);
+        node_id.set_bls_keys(bls_secret_key.secret_key_share(0));
+        check_signer(&node_id, PublicId::Node(node_id.public_id().clone()));
+    }
+
     #[test]
     fn zbase32_encode_decode_client_public_id() {
         let mut rng = rand::thread_rng();
         let id = client::FullId::new_ed25519(&mut rng);
         assert_eq!(
             unwrap!(client::PublicId::decode_from_zbase32(
-                &id.public_id().encode_to_zbase32()
+                id.public_id().encode_to_zbase32()
             )),
             *id.public_id()
         );
 
         let node_id = node::FullId::new(&mut rng);
-        assert!(match client::PublicId::decode_from_zbase32(
-            &node_id.public_id().encode_to_zbase32()
-        ) {
-            Err(Error::FailedToParse(_)) => true,
-            _ => false,
-        });
+        assert!(matches!(
+            client::PublicId::decode_from_zbase32(node_id.public_id().encode_to_zbase32()),
+            Err(Error::FailedToParse(_))
+        ));
         assert!(client::PublicId::decode_from_zbase32("sdkjf832939fjs").is_err());
     }
 
@@ -137,7 +158,7 @@ mod tests {
         id.set_bls_keys(bls_secret_key.secret_key_share(0));
         assert_eq!(
             unwrap!(node::PublicId::decode_from_zbase32(
-                &id.public_id().encode_to_zbase32()
+                id.public_id().encode_to_zbase32()
             )),
             *id.public_id()
         );
@@ -151,7 +172,7 @@ mod tests {
         let id = app::FullId::new_ed25519(&mut rng, owner.public_id().clone());
         assert_eq!(
             unwrap!(app::PublicId::decode_from_zbase32(
-                &id.public_id().encode_to_zbase32()
+                id.public_id().encode_to_zbase32()
             )),
             *id.public_id()
         );
@@ -164,7 +185,7 @@ mod tests {
         let id = PublicId::Client(client::FullId::new_ed25519(&mut rng).public_id().clone());
         assert_eq!(
             id,
-            unwrap!(PublicId::decode_from_zbase32(&id.encode_to_zbase32()))
+            unwrap!(PublicId::decode_from_zbase32(id.encode_to_zbase32()))
         );
         assert!(PublicId::decode_from_zbase32("c419cxim9").is_err());
     }
@@ -8,7 +8,7 @@
 // Software.
 
 use crate::keys::BlsKeypairShare;
-use crate::{utils, Ed25519Digest, Error, PublicKey, Signature, XorName};
+use crate::{utils, Ed25519Digest, Error, PublicKey, Signature, Signer, XorName};
 use ed25519_dalek::{Keypair as Ed25519Keypair, PublicKey as Ed25519PublicKey};
 use hex_fmt::HexFmt;
 use multibase::Decodable;
@@ -103,6 +103,17 @@ impl FullId {
     }
 }
 
+/// Signs using the Ed25519 keypair, since that is what the Node's `name()` is derived from.
+impl Signer for FullId {
+    fn sign(&self, data: &[u8]) -> Signature {
+        self.sign_using_ed25519(data)
+    }
+
+    fn public_key(&self) -> PublicKey {
+        PublicKey::Ed25519(self.ed25519.public)
+    }
+}
+
 /// A struct representing the public identity of a network Node.
 ///
 /// It includes the Ed25519 public key and the optional BLS public key.  This struct also provides
@@ -168,7 +179,7 @@ impl PartialOrd for PublicId {
     }
 }
 
-#[allow(clippy::derive_hash_xor_eq)]
+#[allow(clippy::derived_hash_with_manual_eq)]
 impl Hash for PublicId {
     fn hash<H: Hasher>(&self, state: &mut H) {
         utils::serialise(&self).hash(state)
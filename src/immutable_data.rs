@@ -7,15 +7,11 @@
 // specific language governing permissions and limitations relating to use of the SAFE Network
 // Software.
 
-use crate::{utils, Error, PublicKey, XorName};
+use crate::{utils, Error, Owners, PublicKey, XorName};
 use bincode::serialized_size;
 use multibase::Decodable;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
-use std::{
-    fmt::{self, Debug, Formatter},
-    u64,
-};
-use tiny_keccak;
+use std::fmt::{self, Debug, Formatter};
 
 /// Maximum allowed size for a serialised ImmutableData to grow to.
 pub const MAX_IMMUTABLE_DATA_SIZE_IN_BYTES: u64 = 1024 * 1024 + 10 * 1024;
@@ -31,20 +27,17 @@ pub struct UnpubData {
     value: Vec<u8>,
     /// Contains a set of owners of this data. DataManagers enforce that a DELETE or OWNED-GET type
     /// of request is coming from the MaidManager Authority of the owners.
-    owner: PublicKey,
+    owners: Owners,
 }
 
 impl UnpubData {
     /// Creates a new instance of `UnpubData`.
-    pub fn new(value: Vec<u8>, owner: PublicKey) -> Self {
-        let hash_of_value = tiny_keccak::sha3_256(&value);
-        let serialised_contents = utils::serialise(&(hash_of_value, &owner));
-        let address = Address::Unpub(XorName(tiny_keccak::sha3_256(&serialised_contents)));
-
+    pub fn new(value: Vec<u8>, owners: impl Into<Owners>) -> Self {
+        let owners = owners.into();
         Self {
-            address,
+            address: unpub_address(&value, &owners),
             value,
-            owner,
+            owners,
         }
     }
 
@@ -54,8 +47,8 @@ impl UnpubData {
     }
 
     /// Returns the set of owners.
-    pub fn owner(&self) -> &PublicKey {
-        &self.owner
+    pub fn owners(&self) -> &Owners {
+        &self.owners
     }
 
     /// Returns the address.
@@ -82,18 +75,119 @@ impl UnpubData {
     pub fn validate_size(&self) -> bool {
         self.serialised_size() <= MAX_IMMUTABLE_DATA_SIZE_IN_BYTES
     }
+
+    /// Checks whether `requester` may delete this data, i.e. whether it's an owner whose approval
+    /// alone is enough. Deletion by a threshold of owners is checked with
+    /// `owners().check_approval` instead.
+    ///
+    /// Returns `Err(Error::AccessDenied)` if not. Vaults handle `Request::DeleteUnpubIData` with
+    /// `Data::delete`, which also honours the retention of `RetainedData`.
+    pub fn check_delete_allowed(&self, requester: PublicKey) -> Result<(), Error> {
+        self.owners.check_is_owner(requester)
+    }
 }
 
 impl Serialize for UnpubData {
     fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
-        (&self.value, &self.owner).serialize(serialiser)
+        (&self.value, &self.owners).serialize(serialiser)
     }
 }
 
 impl<'de> Deserialize<'de> for UnpubData {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
-        let (value, owner): (Vec<u8>, PublicKey) = Deserialize::deserialize(deserializer)?;
-        Ok(UnpubData::new(value, owner))
+        let (value, owners): (Vec<u8>, Owners) = Deserialize::deserialize(deserializer)?;
+        Ok(UnpubData::new(value, owners))
+    }
+}
+
+/// Conditions for deleting an `UnpubData`.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Retention {
+    /// The data is shared by this many references, and is only removed once its owner has
+    /// deleted each of them.
+    RefCount(u64),
+    /// The data expires at this network epoch. The owner may still delete it sooner.
+    ExpiresAt(u64),
+}
+
+/// An `UnpubData` together with the conditions for deleting it.
+///
+/// The retention changes over the data's life and isn't part of its address, so it's kept
+/// alongside the content-addressed data rather than in it: it isn't covered by
+/// `verify_against`, and copies of one chunk with different retention are still the same
+/// `UnpubData`.
+#[derive(Hash, Clone, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize, Debug)]
+pub struct RetainedData {
+    data: UnpubData,
+    retention: Retention,
+}
+
+impl RetainedData {
+    /// Creates a new instance of `RetainedData`.
+    pub fn new(data: UnpubData, retention: Retention) -> Self {
+        Self { data, retention }
+    }
+
+    /// Returns the data.
+    pub fn data(&self) -> &UnpubData {
+        &self.data
+    }
+
+    /// Returns the conditions for deleting the data.
+    pub fn retention(&self) -> Retention {
+        self.retention
+    }
+
+    /// Returns the address of the data.
+    pub fn address(&self) -> &Address {
+        self.data.address()
+    }
+
+    /// Handles a deletion by `requester` at network `epoch`, returning `true` if the data should
+    /// now be removed, i.e. unless other references to it remain.
+    ///
+    /// Returns `Err(Error::AccessDenied)` if `requester` may not delete the data, or
+    /// `Err(Error::NoSuchData)` if it has already expired.
+    pub fn delete(&mut self, requester: PublicKey, epoch: u64) -> Result<bool, Error> {
+        self.data.check_delete_allowed(requester)?;
+        if self.is_expired(epoch) {
+            return Err(Error::NoSuchData);
+        }
+        Ok(self.remove_reference())
+    }
+
+    /// Records another reference to the data, e.g. when its owner puts it again. Has no effect
+    /// unless the data is reference counted.
+    pub fn add_reference(&mut self) {
+        if let Retention::RefCount(ref mut count) = self.retention {
+            *count = count.saturating_add(1);
+        }
+    }
+
+    /// Releases one reference to the data, returning `true` if none remain and it should be
+    /// removed. Data which isn't reference counted is always removed.
+    pub fn remove_reference(&mut self) -> bool {
+        match self.retention {
+            Retention::RefCount(ref mut count) => {
+                *count = count.saturating_sub(1);
+                *count == 0
+            }
+            Retention::ExpiresAt(_) => true,
+        }
+    }
+
+    /// Returns `true` if the data has expired by `epoch` and should be removed regardless of any
+    /// request.
+    pub fn is_expired(&self, epoch: u64) -> bool {
+        match self.retention {
+            Retention::ExpiresAt(expiry) => epoch >= expiry,
+            Retention::RefCount(_) => false,
+        }
+    }
+
+    /// Returns the data, dropping the retention.
+    pub fn into_data(self) -> UnpubData {
+        self.data
     }
 }
 
@@ -118,7 +212,7 @@ impl PubData {
     /// Creates a new instance of `ImmutableData`.
     pub fn new(value: Vec<u8>) -> Self {
         Self {
-            address: Address::Pub(XorName(tiny_keccak::sha3_256(&value))),
+            address: pub_address(&value),
             value,
         }
     }
@@ -173,6 +267,154 @@ impl Debug for PubData {
     }
 }
 
+/// Borrowed view of a `UnpubData`, e.g. one being deserialised from a buffer. The address is
+/// calculated over the borrowed value, so it needn't be copied.
+#[derive(Clone, Eq, PartialEq)]
+pub struct UnpubDataRef<'a> {
+    address: Address,
+    value: &'a [u8],
+    owners: Owners,
+}
+
+impl<'a> UnpubDataRef<'a> {
+    /// Creates a new instance of `UnpubDataRef`.
+    pub fn new(value: &'a [u8], owners: impl Into<Owners>) -> Self {
+        let owners = owners.into();
+        Self {
+            address: unpub_address(value, &owners),
+            value,
+            owners,
+        }
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &'a [u8] {
+        self.value
+    }
+
+    /// Returns the set of owners.
+    pub fn owners(&self) -> &Owners {
+        &self.owners
+    }
+
+    /// Returns the address.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        self.address.name()
+    }
+}
+
+impl<'a> From<UnpubDataRef<'a>> for UnpubData {
+    /// Copies the value, reusing the already calculated address.
+    fn from(data: UnpubDataRef<'a>) -> Self {
+        Self {
+            address: data.address,
+            value: data.value.to_vec(),
+            owners: data.owners,
+        }
+    }
+}
+
+impl<'a> Serialize for UnpubDataRef<'a> {
+    fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
+        (self.value, &self.owners).serialize(serialiser)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for UnpubDataRef<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (value, owners): (&'a [u8], Owners) = Deserialize::deserialize(deserializer)?;
+        Ok(UnpubDataRef::new(value, owners))
+    }
+}
+
+impl<'a> Debug for UnpubDataRef<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "UnpubImmutableDataRef {:?}", self.name())
+    }
+}
+
+/// Borrowed view of a `PubData`, e.g. one being deserialised from a buffer. The address is
+/// calculated over the borrowed value, so it needn't be copied.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct PubDataRef<'a> {
+    address: Address,
+    value: &'a [u8],
+}
+
+impl<'a> PubDataRef<'a> {
+    /// Creates a new instance of `PubDataRef`.
+    pub fn new(value: &'a [u8]) -> Self {
+        Self {
+            address: pub_address(value),
+            value,
+        }
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &'a [u8] {
+        self.value
+    }
+
+    /// Returns the address.
+    pub fn address(&self) -> &Address {
+        &self.address
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        self.address.name()
+    }
+}
+
+impl<'a> From<PubDataRef<'a>> for PubData {
+    /// Copies the value, reusing the already calculated address.
+    fn from(data: PubDataRef<'a>) -> Self {
+        Self {
+            address: data.address,
+            value: data.value.to_vec(),
+        }
+    }
+}
+
+impl<'a> Serialize for PubDataRef<'a> {
+    fn serialize<S: Serializer>(&self, serialiser: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serialiser)
+    }
+}
+
+impl<'de: 'a, 'a> Deserialize<'de> for PubDataRef<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value: &'a [u8] = Deserialize::deserialize(deserializer)?;
+        Ok(PubDataRef::new(value))
+    }
+}
+
+impl<'a> Debug for PubDataRef<'a> {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(formatter, "PubImmutableDataRef {:?}", self.name())
+    }
+}
+
+// A single owner is hashed on its own, as before `Owners` existed, so that the data keeps its
+// address. Other owners are hashed including the `Owners` variant.
+fn unpub_address(value: &[u8], owners: &Owners) -> Address {
+    let hash_of_value = tiny_keccak::sha3_256(value);
+    let serialised_contents = match owners {
+        Owners::Single(owner) => utils::serialise(&(hash_of_value, owner)),
+        Owners::Threshold(_) | Owners::BlsGroup(_) => utils::serialise(&(hash_of_value, owners)),
+    };
+    Address::Unpub(XorName(tiny_keccak::sha3_256(&serialised_contents)))
+}
+
+fn pub_address(value: &[u8]) -> Address {
+    Address::Pub(XorName(tiny_keccak::sha3_256(value)))
+}
+
 /// Kind of an ImmutableData.
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
 pub enum Kind {
@@ -264,6 +506,8 @@ pub enum Data {
     Unpub(UnpubData),
     /// Published ImmutableData.
     Pub(PubData),
+    /// Unpublished ImmutableData with the conditions for deleting it.
+    Retained(RetainedData),
 }
 
 impl Data {
@@ -272,6 +516,7 @@ impl Data {
         match self {
             Data::Unpub(data) => data.address(),
             Data::Pub(data) => data.address(),
+            Data::Retained(data) => data.address(),
         }
     }
 
@@ -300,6 +545,19 @@ impl Data {
         match self {
             Data::Unpub(data) => data.value(),
             Data::Pub(data) => data.value(),
+            Data::Retained(data) => data.data().value(),
+        }
+    }
+
+    /// Checks that this data is stored at `address`, i.e. that its content hashes to the name.
+    ///
+    /// The address is recalculated when deserialising, so this detects data which was tampered
+    /// with, returning `Err(Error::IntegrityCheckFailed)`.
+    pub fn verify_against(&self, address: &Address) -> Result<(), Error> {
+        if self.address() == address {
+            Ok(())
+        } else {
+            Err(Error::IntegrityCheckFailed)
         }
     }
 
@@ -308,6 +566,7 @@ impl Data {
         match self {
             Data::Unpub(data) => data.validate_size(),
             Data::Pub(data) => data.validate_size(),
+            Data::Retained(data) => data.data().validate_size(),
         }
     }
 
@@ -316,6 +575,21 @@ impl Data {
         match self {
             Data::Unpub(data) => data.serialised_size(),
             Data::Pub(data) => data.serialised_size(),
+            Data::Retained(data) => serialized_size(data).unwrap_or(u64::MAX),
+        }
+    }
+
+    /// Handles a `Request::DeleteUnpubIData` by `requester` at network `epoch`, returning `true`
+    /// if the data should now be removed. Data with a `Retention` is only removed once no
+    /// references to it remain.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the data is published, `Err(Error::AccessDenied)`
+    /// if `requester` may not delete it, or `Err(Error::NoSuchData)` if it has already expired.
+    pub fn delete(&mut self, requester: PublicKey, epoch: u64) -> Result<bool, Error> {
+        match self {
+            Data::Unpub(data) => data.check_delete_allowed(requester).map(|()| true),
+            Data::Pub(_) => Err(Error::InvalidOperation),
+            Data::Retained(data) => data.delete(requester, epoch),
         }
     }
 }
@@ -332,9 +606,116 @@ impl From<PubData> for Data {
     }
 }
 
+impl From<RetainedData> for Data {
+    fn from(data: RetainedData) -> Self {
+        Data::Retained(data)
+    }
+}
+
+/// Borrowed view of a `RetainedData`, e.g. one being deserialised from a buffer.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub struct RetainedDataRef<'a> {
+    #[serde(borrow)]
+    data: UnpubDataRef<'a>,
+    retention: Retention,
+}
+
+impl<'a> RetainedDataRef<'a> {
+    /// Creates a new instance of `RetainedDataRef`.
+    pub fn new(data: UnpubDataRef<'a>, retention: Retention) -> Self {
+        Self { data, retention }
+    }
+
+    /// Returns the data.
+    pub fn data(&self) -> &UnpubDataRef<'a> {
+        &self.data
+    }
+
+    /// Returns the conditions for deleting the data.
+    pub fn retention(&self) -> Retention {
+        self.retention
+    }
+}
+
+impl<'a> From<RetainedDataRef<'a>> for RetainedData {
+    /// Copies the value, reusing the already calculated address.
+    fn from(data: RetainedDataRef<'a>) -> Self {
+        Self {
+            data: data.data.into(),
+            retention: data.retention,
+        }
+    }
+}
+
+/// Borrowed view of a `Data`, e.g. one being deserialised from a buffer such as the payload of
+/// `Response::GetIData`.
+#[derive(Clone, Eq, PartialEq, Serialize, Deserialize, Debug)]
+pub enum DataRef<'a> {
+    /// Unpublished ImmutableData.
+    Unpub(#[serde(borrow)] UnpubDataRef<'a>),
+    /// Published ImmutableData.
+    Pub(#[serde(borrow)] PubDataRef<'a>),
+    /// Unpublished ImmutableData with the conditions for deleting it.
+    Retained(#[serde(borrow)] RetainedDataRef<'a>),
+}
+
+impl<'a> DataRef<'a> {
+    /// Returns the address.
+    pub fn address(&self) -> &Address {
+        match self {
+            DataRef::Unpub(data) => data.address(),
+            DataRef::Pub(data) => data.address(),
+            DataRef::Retained(data) => data.data().address(),
+        }
+    }
+
+    /// Returns the name.
+    pub fn name(&self) -> &XorName {
+        self.address().name()
+    }
+
+    /// Returns the kind.
+    pub fn kind(&self) -> Kind {
+        self.address().kind()
+    }
+
+    /// Returns the value.
+    pub fn value(&self) -> &'a [u8] {
+        match self {
+            DataRef::Unpub(data) => data.value(),
+            DataRef::Pub(data) => data.value(),
+            DataRef::Retained(data) => data.data().value(),
+        }
+    }
+
+    /// Checks that this data is stored at `address`, i.e. that its content hashes to the name.
+    pub fn verify_against(&self, address: &Address) -> Result<(), Error> {
+        if self.address() == address {
+            Ok(())
+        } else {
+            Err(Error::IntegrityCheckFailed)
+        }
+    }
+}
+
+impl<'a> From<DataRef<'a>> for Data {
+    /// Copies the value, reusing the already calculated address.
+    fn from(data: DataRef<'a>) -> Self {
+        match data {
+            DataRef::Unpub(data) => Data::Unpub(data.into()),
+            DataRef::Pub(data) => Data::Pub(data.into()),
+            DataRef::Retained(data) => Data::Retained(data.into()),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{utils, Address, PubData, PublicKey, UnpubData, XorName};
+    use super::{
+        utils, Address, Data, DataRef, PubData, PubDataRef, PublicKey, RetainedData, Retention,
+        UnpubData, UnpubDataRef, XorName,
+    };
+    use crate::{Ed25519Digest, Error, Owners};
     use bincode::deserialize as deserialise;
     use hex::encode;
     use rand::{self, Rng, SeedableRng};
@@ -363,6 +744,26 @@ mod tests {
         assert_ne!(idata2.name(), idata3.name());
     }
 
+    #[test]
+    fn single_owner_address() {
+        // The address of a single owner's data must not change with the introduction of `Owners`.
+        let secret = unwrap!(ed25519_dalek::SecretKey::from_bytes(&[1; 32]));
+        let owner = PublicKey::Ed25519(ed25519_dalek::PublicKey::from_secret::<Ed25519Digest>(
+            &secret,
+        ));
+        let data = UnpubData::new(b"pinned value".to_vec(), owner);
+        assert_eq!(
+            encode(data.name().0),
+            "a920b79bf69640051e6e7b89cc435b0f0146ec32f21ccff4d05d398b21798cff"
+        );
+
+        let owners = unwrap!(Owners::threshold(iter::once(owner).collect(), 1));
+        assert_ne!(
+            UnpubData::new(b"pinned value".to_vec(), owners).name(),
+            data.name()
+        );
+    }
+
     #[test]
     fn deterministic_test() {
         let value = "immutable data value".to_owned().into_bytes();
@@ -410,7 +811,118 @@ mod tests {
         let name = XorName(rand::random());
         let address = Address::Pub(name);
         let encoded = address.encode_to_zbase32();
-        let decoded = unwrap!(self::Address::decode_from_zbase32(&encoded));
+        let decoded = unwrap!(Address::decode_from_zbase32(&encoded));
         assert_eq!(address, decoded);
     }
+
+    #[test]
+    fn verify_against() {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let pub_data = Data::from(PubData::new(b"value".to_vec()));
+        let unpub_data = Data::from(UnpubData::new(b"value".to_vec(), owner));
+
+        unwrap!(pub_data.verify_against(&Address::Pub(*pub_data.name())));
+        unwrap!(unpub_data.verify_against(&Address::Unpub(*unpub_data.name())));
+
+        // Same name, different kind.
+        assert_eq!(
+            pub_data.verify_against(&Address::Unpub(*pub_data.name())),
+            Err(Error::IntegrityCheckFailed)
+        );
+
+        // Tampered value deserialises to a different name.
+        let mut serialised = utils::serialise(&pub_data);
+        *unwrap!(serialised.last_mut()) ^= 1;
+        let tampered: Data = unwrap!(deserialise(&serialised));
+        assert_eq!(
+            tampered.verify_against(pub_data.address()),
+            Err(Error::IntegrityCheckFailed)
+        );
+    }
+
+    #[test]
+    fn borrowed_deserialisation() {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let pub_data = PubData::new(b"published value".to_vec());
+        let unpub_data = UnpubData::new(b"unpublished value".to_vec(), owner);
+
+        let serialised = utils::serialise(&pub_data);
+        let borrowed: PubDataRef = unwrap!(deserialise(&serialised));
+        assert_eq!(borrowed.address(), pub_data.address());
+        assert!(serialised
+            .as_ptr_range()
+            .contains(&borrowed.value().as_ptr()));
+        assert_eq!(utils::serialise(&borrowed), serialised);
+        assert_eq!(PubData::from(borrowed), pub_data);
+
+        let serialised = utils::serialise(&unpub_data);
+        let borrowed: UnpubDataRef = unwrap!(deserialise(&serialised));
+        assert_eq!(borrowed.address(), unpub_data.address());
+        assert_eq!(borrowed.owners(), unpub_data.owners());
+        assert!(serialised
+            .as_ptr_range()
+            .contains(&borrowed.value().as_ptr()));
+        assert_eq!(utils::serialise(&borrowed), serialised);
+        assert_eq!(UnpubData::from(borrowed), unpub_data);
+
+        let retained_data = RetainedData::new(unpub_data.clone(), Retention::RefCount(3));
+        for data in &[
+            Data::from(pub_data),
+            Data::from(unpub_data),
+            Data::from(retained_data),
+        ] {
+            let serialised = utils::serialise(data);
+            let borrowed: DataRef = unwrap!(deserialise(&serialised));
+            assert_eq!(borrowed.address(), data.address());
+            assert!(serialised
+                .as_ptr_range()
+                .contains(&borrowed.value().as_ptr()));
+            assert_eq!(utils::serialise(&borrowed), serialised);
+            unwrap!(borrowed.verify_against(data.address()));
+            assert_eq!(Data::from(borrowed), *data);
+        }
+    }
+
+    #[test]
+    fn deletion() {
+        let owner = PublicKey::Bls(SecretKey::random().public_key());
+        let other = PublicKey::Bls(SecretKey::random().public_key());
+        let value = b"value".to_vec();
+
+        let data = UnpubData::new(value, owner);
+        unwrap!(data.check_delete_allowed(owner));
+        assert_eq!(data.check_delete_allowed(other), Err(Error::AccessDenied));
+
+        // Retention is kept alongside the data, and survives serialisation.
+        let mut retained = RetainedData::new(data.clone(), Retention::RefCount(1));
+        let parsed: RetainedData = unwrap!(deserialise(&utils::serialise(&retained)));
+        assert_eq!(parsed, retained);
+        assert!(!retained.is_expired(u64::MAX));
+
+        retained.add_reference();
+        assert!(!retained.remove_reference());
+        assert_eq!(retained.retention(), Retention::RefCount(1));
+        assert_eq!(retained.data(), &data);
+        assert!(retained.remove_reference());
+
+        let retained = RetainedData::new(data.clone(), Retention::ExpiresAt(10));
+        assert!(!retained.is_expired(9));
+        assert!(retained.is_expired(10));
+
+        // Deleting honours the retention.
+        let mut stored = Data::from(RetainedData::new(data.clone(), Retention::RefCount(2)));
+        assert_eq!(stored.delete(other, 0), Err(Error::AccessDenied));
+        assert_eq!(stored.delete(owner, 0), Ok(false));
+        assert_eq!(stored.delete(owner, 0), Ok(true));
+
+        let mut stored = Data::from(retained);
+        assert_eq!(stored.delete(owner, 9), Ok(true));
+        assert_eq!(stored.delete(owner, 10), Err(Error::NoSuchData));
+
+        assert_eq!(Data::from(data).delete(owner, 0), Ok(true));
+        assert_eq!(
+            Data::from(PubData::new(b"value".to_vec())).delete(owner, 0),
+            Err(Error::InvalidOperation)
+        );
+    }
 }
@@ -14,7 +14,6 @@
 //! secret key.
 
 use crate::{utils, Ed25519Digest, Error, Result, XorName, XOR_NAME_LEN};
-use ed25519_dalek;
 use hex_fmt::HexFmt;
 use multibase::Decodable;
 use rand::{CryptoRng, Rng};
@@ -95,7 +94,7 @@ impl PublicKey {
     }
 }
 
-#[allow(clippy::derive_hash_xor_eq)]
+#[allow(clippy::derived_hash_with_manual_eq)]
 impl Hash for PublicKey {
     fn hash<H: Hasher>(&self, state: &mut H) {
         utils::serialise(&self).hash(state)
@@ -210,7 +209,7 @@ impl From<threshold_crypto::SignatureShare> for Signature {
     }
 }
 
-#[allow(clippy::derive_hash_xor_eq)]
+#[allow(clippy::derived_hash_with_manual_eq)]
 impl Hash for Signature {
     fn hash<H: Hasher>(&self, state: &mut H) {
         utils::serialise(&self).hash(state)
@@ -329,6 +328,39 @@ impl Keypair {
             Self::BlsShare(keypair) => PublicKey::BlsShare(keypair.public),
         }
     }
+
+    /// Creates a detached signature of `data`.
+    pub fn sign<T: AsRef<[u8]>>(&self, data: T) -> Signature {
+        match self {
+            Self::Ed25519(keypair) => {
+                Signature::Ed25519(keypair.sign::<Ed25519Digest>(data.as_ref()))
+            }
+            Self::Bls(keypair) => Signature::Bls(keypair.secret.inner().sign(data)),
+            Self::BlsShare(keypair) => Signature::BlsShare(keypair.secret.inner().sign(data)),
+        }
+    }
+}
+
+/// An entity able to sign data with a secret key.
+///
+/// This is implemented by `Keypair` and by all the full ID types, so that code which needs to sign
+/// something (e.g. a request) can be generic over the identity doing the signing.
+pub trait Signer {
+    /// Creates a detached signature of `data`.
+    fn sign(&self, data: &[u8]) -> Signature;
+
+    /// Returns the public key which can be used to verify signatures created by `sign`.
+    fn public_key(&self) -> PublicKey;
+}
+
+impl Signer for Keypair {
+    fn sign(&self, data: &[u8]) -> Signature {
+        Keypair::sign(self, data)
+    }
+
+    fn public_key(&self) -> PublicKey {
+        Keypair::public_key(self)
+    }
 }
 
 /// BLS keypair.
@@ -354,7 +386,6 @@ mod tests {
     use super::*;
     use crate::utils;
     use bincode::deserialize as deserialise;
-    use rand;
     use threshold_crypto::{self, IntoFr};
 
     fn gen_keypairs() -> Vec<Keypair> {
@@ -380,7 +411,7 @@ mod tests {
         for key in keys {
             assert_eq!(
                 key,
-                unwrap!(PublicKey::decode_from_zbase32(&key.encode_to_zbase32()))
+                unwrap!(PublicKey::decode_from_zbase32(key.encode_to_zbase32()))
             );
         }
     }
@@ -398,6 +429,21 @@ mod tests {
         }
     }
 
+    #[test]
+    fn sign_and_verify() {
+        let data = b"hello";
+
+        for keypair in gen_keypairs() {
+            let signature = keypair.sign(data);
+            unwrap!(keypair.public_key().verify(&signature, data));
+
+            // The `Signer` impl produces signatures verifiable with its public key.
+            let signer: &dyn Signer = &keypair;
+            let signature = signer.sign(data);
+            unwrap!(signer.public_key().verify(&signature, data));
+        }
+    }
+
     // Test serialising and deserialising key pairs.
     #[test]
     fn serialisation_key_pair() {
@@ -29,28 +29,42 @@
 )]
 
 mod append_only_data;
+mod clock;
 mod coins;
+mod data_map;
 mod errors;
+#[cfg(test)]
+mod golden;
 mod identity;
 mod immutable_data;
 mod keys;
+mod message_id_cache;
 mod mutable_data;
+mod owners;
+mod pending_requests;
 mod request;
 mod response;
+mod safe_url;
 mod utils;
+mod wire;
 
 pub use append_only_data::{
     Action as ADataAction, Address as ADataAddress, AppendOnlyData,
-    AppendOperation as ADataAppendOperation, Data as AData, Entries as ADataEntries,
-    Entry as ADataEntry, Index as ADataIndex, Indices as ADataIndices, Kind as ADataKind,
-    Owner as ADataOwner, Permissions as ADataPermissions,
+    AppendOperation as ADataAppendOperation, Cursor as ADataCursor, Data as AData,
+    Entries as ADataEntries, Entry as ADataEntry, Index as ADataIndex, Indices as ADataIndices,
+    Kind as ADataKind, Owner as ADataOwner, Page as ADataPage, Permissions as ADataPermissions,
     PubPermissionSet as ADataPubPermissionSet, PubPermissions as ADataPubPermissions,
     PubSeqData as PubSeqAppendOnlyData, PubUnseqData as PubUnseqAppendOnlyData, SeqAppendOnly,
     UnpubPermissionSet as ADataUnpubPermissionSet, UnpubPermissions as ADataUnpubPermissions,
     UnpubSeqData as UnpubSeqAppendOnlyData, UnpubUnseqData as UnpubUnseqAppendOnlyData,
     UnseqAppendOnly, User as ADataUser,
 };
-pub use coins::Coins;
+pub use clock::{Clock, SystemClock};
+pub use coins::{Coins, PricingPolicy};
+pub use data_map::{
+    ChunkInfo, ChunkStore, Chunker, DataMap, EncryptedChunkInfo, MAX_CHUNK_SIZE,
+    MAX_DATA_MAP_NESTING, MIN_CHUNK_SIZE,
+};
 pub use errors::{EntryError, Error, Result};
 pub use identity::{
     app::{FullId as AppFullId, PublicId as AppPublicId},
@@ -59,23 +73,36 @@ pub use identity::{
     PublicId,
 };
 pub use immutable_data::{
-    Address as IDataAddress, Data as IData, Kind as IDataKind, PubData as PubImmutableData,
-    UnpubData as UnpubImmutableData, MAX_IMMUTABLE_DATA_SIZE_IN_BYTES,
+    Address as IDataAddress, Data as IData, DataRef as IDataRef, Kind as IDataKind,
+    PubData as PubImmutableData, PubDataRef as PubImmutableDataRef,
+    RetainedData as RetainedUnpubImmutableData, RetainedDataRef as RetainedUnpubImmutableDataRef,
+    Retention as IDataRetention, UnpubData as UnpubImmutableData,
+    UnpubDataRef as UnpubImmutableDataRef, MAX_IMMUTABLE_DATA_SIZE_IN_BYTES,
 };
-pub use keys::{BlsKeypair, BlsKeypairShare, Keypair, PublicKey, Signature};
+pub use keys::{BlsKeypair, BlsKeypairShare, Keypair, PublicKey, Signature, Signer};
+pub use message_id_cache::MessageIdCache;
 pub use mutable_data::{
-    Action as MDataAction, Address as MDataAddress, Data as MData, Entries as MDataEntries,
-    EntryActions as MDataEntryActions, Kind as MDataKind, PermissionSet as MDataPermissionSet,
+    Action as MDataAction, Address as MDataAddress, Cursor as MDataCursor, Data as MData,
+    Diff as MDataDiff, Entries as MDataEntries, EntryActions as MDataEntryActions,
+    HistoryPolicy as MDataHistoryPolicy, Kind as MDataKind, MergeReport as MDataMergeReport,
+    Page as MDataPage, PermissionSet as MDataPermissionSet, Revision as MDataRevision,
     SeqData as SeqMutableData, SeqEntries as MDataSeqEntries,
     SeqEntryAction as MDataSeqEntryAction, SeqEntryActions as MDataSeqEntryActions,
     SeqValue as MDataSeqValue, UnseqData as UnseqMutableData, UnseqEntries as MDataUnseqEntries,
     UnseqEntryAction as MDataUnseqEntryAction, UnseqEntryActions as MDataUnseqEntryActions,
     Value as MDataValue, Values as MDataValues,
 };
-pub use request::{LoginPacket, Request, Type as RequestType, MAX_LOGIN_PACKET_BYTES};
-pub use response::{Response, TryFromError};
+pub use owners::{Approvals, Owners, ThresholdOwners};
+pub use pending_requests::PendingRequests;
+pub use request::{
+    BatchMode, LoginPacket, Request, SignedRequest, SubscriptionTarget, Type as RequestType,
+    MAX_LOGIN_PACKET_BYTES,
+};
+pub use response::{IfModified, Response, TryFromError};
+pub use safe_url::SafeUrl;
 pub use sha3::Sha3_512 as Ed25519Digest;
 pub use utils::verify_signature;
+pub use wire::{PROTOCOL_VERSION, WIRE_MAGIC};
 
 use hex_fmt::HexFmt;
 use multibase::Decodable;
@@ -114,6 +141,29 @@ impl Data {
     pub fn is_unpub(&self) -> bool {
         !self.is_pub()
     }
+
+    /// Returns the address of the data.
+    pub fn address(&self) -> DataAddress {
+        match *self {
+            Self::Immutable(ref idata) => DataAddress::Immutable(*idata.address()),
+            Self::Mutable(ref mdata) => DataAddress::Mutable(*mdata.address()),
+            Self::AppendOnly(ref adata) => DataAddress::AppendOnly(*adata.address()),
+        }
+    }
+
+    /// Returns the name of the data.
+    pub fn name(&self) -> &XorName {
+        match *self {
+            Self::Immutable(ref idata) => idata.name(),
+            Self::Mutable(ref mdata) => mdata.name(),
+            Self::AppendOnly(ref adata) => adata.name(),
+        }
+    }
+
+    /// Returns the kind of the data.
+    pub fn kind(&self) -> DataKind {
+        self.address().kind()
+    }
 }
 
 impl From<IData> for Data {
@@ -134,6 +184,111 @@ impl From<AData> for Data {
     }
 }
 
+/// Kind of an ImmutableData, MutableData or AppendOnlyData.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum DataKind {
+    /// Kind of ImmutableData.
+    Immutable(IDataKind),
+    /// Kind of MutableData.
+    Mutable(MDataKind),
+    /// Kind of AppendOnlyData.
+    AppendOnly(ADataKind),
+}
+
+impl DataKind {
+    /// Returns true if published.
+    pub fn is_pub(self) -> bool {
+        match self {
+            Self::Immutable(kind) => kind.is_pub(),
+            Self::Mutable(_) => false,
+            Self::AppendOnly(kind) => kind.is_pub(),
+        }
+    }
+
+    /// Returns true if unpublished.
+    pub fn is_unpub(self) -> bool {
+        !self.is_pub()
+    }
+}
+
+/// Address of an ImmutableData, MutableData or AppendOnlyData.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum DataAddress {
+    /// Address of ImmutableData.
+    Immutable(IDataAddress),
+    /// Address of MutableData.
+    Mutable(MDataAddress),
+    /// Address of AppendOnlyData.
+    AppendOnly(ADataAddress),
+}
+
+impl DataAddress {
+    /// Returns the kind of the data.
+    pub fn kind(&self) -> DataKind {
+        match *self {
+            Self::Immutable(address) => DataKind::Immutable(address.kind()),
+            Self::Mutable(address) => DataKind::Mutable(address.kind()),
+            Self::AppendOnly(address) => DataKind::AppendOnly(address.kind()),
+        }
+    }
+
+    /// Returns the name of the data.
+    pub fn name(&self) -> &XorName {
+        match *self {
+            Self::Immutable(ref address) => address.name(),
+            Self::Mutable(ref address) => address.name(),
+            Self::AppendOnly(ref address) => address.name(),
+        }
+    }
+
+    /// Returns the type tag of the data, or `None` for ImmutableData, which has no tag.
+    pub fn tag(&self) -> Option<u64> {
+        match *self {
+            Self::Immutable(_) => None,
+            Self::Mutable(address) => Some(address.tag()),
+            Self::AppendOnly(address) => Some(address.tag()),
+        }
+    }
+
+    /// Returns true if published.
+    pub fn is_pub(&self) -> bool {
+        self.kind().is_pub()
+    }
+
+    /// Returns true if unpublished.
+    pub fn is_unpub(&self) -> bool {
+        !self.is_pub()
+    }
+
+    /// Returns the `DataAddress` serialised and encoded in z-base-32.
+    pub fn encode_to_zbase32(&self) -> String {
+        utils::encode(&self)
+    }
+
+    /// Creates from z-base-32 encoded string.
+    pub fn decode_from_zbase32<I: Decodable>(encoded: I) -> Result<Self> {
+        utils::decode(encoded)
+    }
+}
+
+impl From<IDataAddress> for DataAddress {
+    fn from(address: IDataAddress) -> Self {
+        Self::Immutable(address)
+    }
+}
+
+impl From<MDataAddress> for DataAddress {
+    fn from(address: MDataAddress) -> Self {
+        Self::Mutable(address)
+    }
+}
+
+impl From<ADataAddress> for DataAddress {
+    fn from(address: ADataAddress) -> Self {
+        Self::AppendOnly(address)
+    }
+}
+
 /// Permissions for an app stored by the Client Handlers.
 #[derive(
     Copy, Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Default, Debug,
@@ -147,6 +302,10 @@ pub struct AppPermissions {
     pub get_balance: bool,
 }
 
+/// Maximum number of items in a page of a paginated listing. A cursor with a greater limit gets
+/// pages of this many items.
+pub const MAX_PAGE_LIMIT: u64 = 1000;
+
 /// Constant byte length of `XorName`.
 pub const XOR_NAME_LEN: usize = 32;
 
@@ -195,7 +354,7 @@ impl Distribution<XorName> for Standard {
 /// Wrapper message that contains a message ID and the requester ID along the request or response.
 /// It should also contain a valid signature if it's sent by the owner(s).
 #[allow(clippy::large_enum_variant)]
-#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize)]
+#[derive(Hash, Eq, PartialEq, Clone, Serialize, Deserialize, Debug)]
 pub enum Message {
     /// Request with the message ID.
     Request {
@@ -203,8 +362,13 @@ pub enum Message {
         request: Request,
         /// Associated message ID.
         message_id: MessageId,
-        /// Signature of `(request, message_id)`. Optional if the request is read-only.
+        /// Signature of `(request, message_id)`. Optional if the request is read-only (see
+        /// `Request::requires_signature`).
         signature: Option<Signature>,
+        /// Owners' approvals of `(request, message_id)`, for a request which only the owners may
+        /// make when the requester's signature alone isn't enough (see
+        /// `Request::requires_owners_approval`).
+        approvals: Option<Approvals>,
     },
     /// Response matched to the message ID.
     Response {
@@ -213,7 +377,7 @@ pub enum Message {
         /// Associated message ID.
         message_id: MessageId,
     },
-    /// Notification of a transaction.
+    /// Notification of a transaction or of a change to subscribed data.
     Notification {
         /// Notification.
         notification: Notification,
@@ -230,13 +394,60 @@ impl Message {
             Self::Notification { .. } => None,
         }
     }
+
+    /// Verifies the signature of a `Message::Request` against the requester's public key.
+    ///
+    /// Returns `Err(Error::InvalidSignature)` if the signature is invalid, or if it's missing from a
+    /// request which must be signed. Read-only requests without a signature, as well as responses
+    /// and notifications (which are never signed), are accepted. Returns
+    /// `Err(Error::InvalidOperation)` if the request fails `Request::validate`.
+    ///
+    /// If the request needs the owners' approval (see `Request::requires_owners_approval`),
+    /// `owners` must be the owners of the data it targets. Unless the requester alone is enough
+    /// (see `Owners::check_is_owner`), the message's approvals are then checked against them,
+    /// returning `Err(Error::AccessDenied)` if they are missing or too few.
+    pub fn verify(&self, requester: &PublicId, owners: Option<&Owners>) -> Result<()> {
+        let (request, message_id, signature, approvals) = match self {
+            Self::Request {
+                request,
+                message_id,
+                signature,
+                approvals,
+            } => (request, message_id, signature, approvals),
+            Self::Response { .. } | Self::Notification { .. } => return Ok(()),
+        };
+
+        request.validate()?;
+        match signature {
+            Some(signature) => {
+                verify_signature(signature, &requester.public_key(), request, message_id)?
+            }
+            None => {
+                if request.requires_signature() {
+                    return Err(Error::InvalidSignature);
+                }
+            }
+        }
+
+        if !request.requires_owners_approval() {
+            return Ok(());
+        }
+        let owners = owners.ok_or(Error::InvalidOperation)?;
+        if owners.check_is_owner(requester.public_key()).is_ok() {
+            return Ok(());
+        }
+        approvals
+            .as_ref()
+            .ok_or(Error::AccessDenied)?
+            .check(owners, &utils::serialise(&(request, message_id)))
+    }
 }
 
 /// Unique ID for messages.
 ///
 /// This is used for deduplication: Since the network sends messages redundantly along different
 /// routes, the same message will usually arrive more than once at any given node. A message with
-/// an ID that is already in the cache will be ignored.
+/// an ID that is already in the cache (see `MessageIdCache`) will be ignored.
 #[derive(Ord, PartialOrd, Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, Hash)]
 pub struct MessageId(pub XorName);
 
@@ -292,13 +503,80 @@ pub struct Transaction {
     pub amount: Coins,
 }
 
-/// Notification of a transaction.
+/// Notification sent to a client without it having made a request.
 #[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
-pub struct Notification(pub Transaction);
+pub enum Notification {
+    /// Coins have been transferred to the client's balance.
+    Transaction(Transaction),
+    /// MutableData the client has subscribed to has changed.
+    MDataChanged {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Which part of the data changed.
+        change: DataChange,
+        /// Content version of the data after the change, as compared by `GetMDataIfNewer`. See
+        /// `MData::content_version`.
+        version: u64,
+        /// ID of the client or app whose request changed the data.
+        mutator: PublicId,
+    },
+    /// AppendOnlyData the client has subscribed to has changed.
+    ADataChanged {
+        /// AppendOnlyData address.
+        address: ADataAddress,
+        /// Which part of the data changed.
+        change: DataChange,
+        /// Indices of the data after the change.
+        indices: ADataIndices,
+        /// ID of the client or app whose request changed the data.
+        mutator: PublicId,
+    },
+}
+
+impl Notification {
+    /// Creates the notification of a change to `data`, which is already mutated.
+    pub fn mdata_changed(data: &MData, change: DataChange, mutator: PublicId) -> Self {
+        Notification::MDataChanged {
+            address: *data.address(),
+            change,
+            version: data.content_version(),
+            mutator,
+        }
+    }
+
+    /// Creates the notification of a change to `data`, which is already mutated.
+    pub fn adata_changed(data: &AData, change: DataChange, mutator: PublicId) -> Self {
+        Notification::ADataChanged {
+            address: *data.address(),
+            change,
+            indices: ADataIndices::new(
+                data.entries_index(),
+                data.owners_index(),
+                data.permissions_index(),
+            ),
+            mutator,
+        }
+    }
+}
+
+/// Part of MutableData or AppendOnlyData which changed, as reported by a `Notification`.
+#[derive(Copy, Clone, Hash, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Debug)]
+pub enum DataChange {
+    /// Entries were inserted, updated, deleted or appended.
+    Entries,
+    /// Permissions were set or deleted.
+    Permissions,
+    /// The owner was changed.
+    Owner,
+}
 
 #[cfg(test)]
 mod tests {
-    use crate::XorName;
+    use crate::{
+        AData, ADataAddress, ADataEntry, ADataKind, ClientFullId, Data, DataAddress, DataChange,
+        DataKind, IData, IDataAddress, MData, MDataAddress, MDataUnseqEntryActions, Notification,
+        PubImmutableData, PubSeqAppendOnlyData, PublicId, SeqAppendOnly, UnseqMutableData, XorName,
+    };
     use unwrap::unwrap;
 
     #[test]
@@ -308,4 +586,84 @@ mod tests {
         let decoded = unwrap!(XorName::decode_from_zbase32(&encoded));
         assert_eq!(name, decoded);
     }
+
+    #[test]
+    fn zbase32_encode_decode_data_address() {
+        let name = XorName(rand::random());
+        let addresses = vec![
+            DataAddress::Immutable(IDataAddress::Unpub(name)),
+            DataAddress::Mutable(MDataAddress::Seq { name, tag: 15000 }),
+            DataAddress::AppendOnly(ADataAddress::PubUnseq { name, tag: 10000 }),
+        ];
+        for address in addresses {
+            let encoded = address.encode_to_zbase32();
+            let decoded = unwrap!(DataAddress::decode_from_zbase32(&encoded));
+            assert_eq!(address, decoded);
+        }
+    }
+
+    #[test]
+    fn data_address() {
+        let idata = PubImmutableData::new(b"value".to_vec());
+        let data = Data::from(IData::from(idata.clone()));
+        let address = data.address();
+        assert_eq!(address, DataAddress::Immutable(*idata.address()));
+        assert_eq!(address.name(), idata.name());
+        assert_eq!(address.tag(), None);
+        assert!(address.is_pub());
+
+        let name = XorName(rand::random());
+        let data = Data::from(AData::from(PubSeqAppendOnlyData::new(name, 10000)));
+        let address = data.address();
+        assert_eq!(address, ADataAddress::PubSeq { name, tag: 10000 }.into());
+        assert_eq!(*address.name(), name);
+        assert_eq!(address.tag(), Some(10000));
+        assert_eq!(data.kind(), DataKind::AppendOnly(ADataKind::PubSeq));
+        assert!(data.kind().is_pub());
+        assert!(DataAddress::Mutable(MDataAddress::Unseq { name, tag: 1 }).is_unpub());
+    }
+
+    #[test]
+    fn mdata_changed_notification() {
+        let client_id = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let owner = *client_id.public_id().public_key();
+        let mut data = UnseqMutableData::new(XorName(rand::random()), 10000, owner);
+        unwrap!(data.mutate_entries(
+            MDataUnseqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec()),
+            owner
+        ));
+        let data = MData::from(data);
+
+        let mutator = PublicId::Client(client_id.public_id().clone());
+        match Notification::mdata_changed(&data, DataChange::Entries, mutator) {
+            Notification::MDataChanged {
+                address, version, ..
+            } => {
+                assert_eq!(address, *data.address());
+                assert_eq!(version, 1);
+                assert!(!data.is_newer_than(version));
+            }
+            notification => panic!("Unexpected notification: {:?}", notification),
+        }
+    }
+
+    #[test]
+    fn adata_changed_notification() {
+        let client_id = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let mut data = PubSeqAppendOnlyData::new(XorName(rand::random()), 10000);
+        unwrap!(data.append(vec![ADataEntry::new(b"key".to_vec(), b"value".to_vec())], 0));
+        let data = AData::from(data);
+
+        let mutator = PublicId::Client(client_id.public_id().clone());
+        match Notification::adata_changed(&data, DataChange::Entries, mutator) {
+            Notification::ADataChanged {
+                address, indices, ..
+            } => {
+                assert_eq!(address, *data.address());
+                assert_eq!(indices, unwrap!(data.indices()));
+                assert!(!data.is_newer_than(&indices));
+            }
+            notification => panic!("Unexpected notification: {:?}", notification),
+        }
+    }
 }
@@ -0,0 +1,182 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Clock, Error, MessageId, Result, SystemClock};
+use std::{
+    collections::{HashMap, VecDeque},
+    time::{Duration, Instant},
+};
+
+/// Bounded cache of recently seen `MessageId`s, used to drop duplicate messages.
+///
+/// An ID is forgotten once it has been in the cache for longer than the time-to-live, or when the
+/// cache is full and the ID is the oldest one in it.
+pub struct MessageIdCache<C: Clock = SystemClock> {
+    clock: C,
+    capacity: usize,
+    time_to_live: Duration,
+    // IDs along with the time they were inserted.
+    ids: HashMap<MessageId, Instant>,
+    // IDs in insertion order, along with the time they were inserted.
+    queue: VecDeque<(Instant, MessageId)>,
+    hits: u64,
+    misses: u64,
+}
+
+impl MessageIdCache<SystemClock> {
+    /// Constructs a new cache holding at most `capacity` IDs, each for at most `time_to_live`.
+    ///
+    /// A `capacity` of zero disables the cache, i.e. no duplicates are ever detected.
+    pub fn new(capacity: usize, time_to_live: Duration) -> Self {
+        Self::with_clock(capacity, time_to_live, SystemClock)
+    }
+}
+
+impl<C: Clock> MessageIdCache<C> {
+    /// Constructs a new cache which uses `clock` as its source of time.
+    pub fn with_clock(capacity: usize, time_to_live: Duration, clock: C) -> Self {
+        Self {
+            clock,
+            capacity,
+            time_to_live,
+            ids: HashMap::new(),
+            queue: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Inserts `message_id` into the cache.
+    ///
+    /// Returns `Err(Error::DuplicateMessageId)` if it is already in the cache, in which case the
+    /// message should be dropped.
+    pub fn insert_or_reject(&mut self, message_id: MessageId) -> Result<()> {
+        let now = self.clock.now();
+        self.remove_expired(now);
+
+        if self.ids.contains_key(&message_id) {
+            self.hits += 1;
+            return Err(Error::DuplicateMessageId);
+        }
+        self.misses += 1;
+
+        if self.capacity == 0 {
+            return Ok(());
+        }
+        while self.queue.len() >= self.capacity {
+            if let Some((_, oldest)) = self.queue.pop_front() {
+                let _ = self.ids.remove(&oldest);
+            }
+        }
+
+        let _ = self.ids.insert(message_id, now);
+        self.queue.push_back((now, message_id));
+        Ok(())
+    }
+
+    /// Returns `true` if `message_id` is in the cache and hasn't expired.
+    pub fn contains(&self, message_id: &MessageId) -> bool {
+        let now = self.clock.now();
+        self.ids
+            .get(message_id)
+            .is_some_and(|inserted| !self.is_expired(*inserted, now))
+    }
+
+    /// Returns the number of IDs in the cache, including any that expired since the last insertion.
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns `true` if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Returns the number of IDs rejected as duplicates.
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    /// Returns the number of IDs accepted as new.
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    fn is_expired(&self, inserted: Instant, now: Instant) -> bool {
+        now.duration_since(inserted) >= self.time_to_live
+    }
+
+    fn remove_expired(&mut self, now: Instant) {
+        while let Some(&(inserted, id)) = self.queue.front() {
+            if !self.is_expired(inserted, now) {
+                break;
+            }
+            let _ = self.queue.pop_front();
+            let _ = self.ids.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use unwrap::unwrap;
+
+    #[test]
+    fn rejects_duplicates() {
+        let mut cache = MessageIdCache::new(10, Duration::from_secs(60));
+        let id = MessageId::new();
+
+        unwrap!(cache.insert_or_reject(id));
+        assert_eq!(cache.insert_or_reject(id), Err(Error::DuplicateMessageId));
+        unwrap!(cache.insert_or_reject(MessageId::new()));
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.hits(), 1);
+        assert_eq!(cache.misses(), 2);
+    }
+
+    #[test]
+    fn evicts_oldest_when_full() {
+        let mut cache = MessageIdCache::new(2, Duration::from_secs(60));
+        let ids: Vec<_> = (0..3).map(|_| MessageId::new()).collect();
+
+        for id in &ids {
+            unwrap!(cache.insert_or_reject(*id));
+        }
+
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.contains(&ids[0]));
+        assert!(cache.contains(&ids[1]));
+        assert!(cache.contains(&ids[2]));
+
+        // The evicted ID is accepted again.
+        unwrap!(cache.insert_or_reject(ids[0]));
+    }
+
+    #[test]
+    fn expires_after_time_to_live() {
+        let clock = MockClock::new();
+        let mut cache = MessageIdCache::with_clock(10, Duration::from_secs(60), clock.clone());
+        let id_0 = MessageId::new();
+        let id_1 = MessageId::new();
+
+        unwrap!(cache.insert_or_reject(id_0));
+        clock.advance(Duration::from_secs(30));
+        unwrap!(cache.insert_or_reject(id_1));
+
+        clock.advance(Duration::from_secs(30));
+        assert!(!cache.contains(&id_0));
+        assert!(cache.contains(&id_1));
+
+        unwrap!(cache.insert_or_reject(id_0));
+        assert_eq!(cache.insert_or_reject(id_1), Err(Error::DuplicateMessageId));
+    }
+}
@@ -27,14 +27,18 @@
 //! does not have to pass version numbers for keys, but it still must pass the next version number
 //! while modifying the MutableData shell.
 
-use crate::{utils, EntryError, Error, PublicKey, Result, XorName};
+use crate::{
+    utils, EntryError, Error, Owners, PublicKey, Result, Signature, XorName, MAX_PAGE_LIMIT,
+};
 use hex_fmt::HexFmt;
 use multibase::Decodable;
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp::{self, Ordering},
     collections::{btree_map::Entry, BTreeMap, BTreeSet},
     fmt::{self, Debug, Formatter},
     mem,
+    ops::Bound,
 };
 
 /// MutableData that is unpublished on the network. This data can only be fetched by the owner or
@@ -49,10 +53,17 @@ pub struct SeqData {
     permissions: BTreeMap<PublicKey, PermissionSet>,
     /// Version should be increased for any changes to MutableData fields except for data.
     version: u64,
-    /// Contains the public key of an owner or owners of this data.
+    /// Incremented by every change to the data, including its entries.
+    content_version: u64,
+    /// Contains the public key of an owner, a threshold of owners or a BLS group owning this data.
     ///
     /// Data Handlers in vaults enforce that a mutation request has a valid signature of the owner.
-    owner: PublicKey,
+    owners: Owners,
+    /// Superseded revisions of the entries, if history is enabled.
+    history: Option<History>,
+    /// Versions at which deleted entries were deleted, so that they can't be re-inserted with a
+    /// stale version.
+    tombstones: BTreeMap<Vec<u8>, u64>,
 }
 
 impl Debug for SeqData {
@@ -73,10 +84,12 @@ pub struct UnseqData {
     permissions: BTreeMap<PublicKey, PermissionSet>,
     /// Version should be increased for any changes to MutableData fields except for data.
     version: u64,
-    /// Contains the public key of an owner or owners of this data.
+    /// Incremented by every change to the data, including its entries.
+    content_version: u64,
+    /// Contains the public key of an owner, a threshold of owners or a BLS group owning this data.
     ///
     /// Data Handlers in vaults enforce that a mutation request has a valid signature of the owner.
-    owner: PublicKey,
+    owners: Owners,
 }
 
 impl Debug for UnseqData {
@@ -142,6 +155,94 @@ impl From<Vec<Vec<u8>>> for Values {
     }
 }
 
+/// A revision of an entry in sequenced MutableData with history enabled.
+#[derive(Debug, Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
+pub enum Revision {
+    /// The entry held this value.
+    Value(SeqValue),
+    /// The entry was deleted at this version.
+    Deleted(u64),
+}
+
+impl Revision {
+    /// Returns the version of the entry at this revision.
+    pub fn version(&self) -> u64 {
+        match self {
+            Revision::Value(value) => value.version,
+            Revision::Deleted(version) => *version,
+        }
+    }
+
+    // Orders conflicting revisions for merging: the higher version wins, then a deletion wins
+    // over a value, then the greater value.
+    fn merge_order(&self) -> (u64, bool, Option<&[u8]>) {
+        match self {
+            Revision::Value(value) => (value.version, false, Some(&value.data)),
+            Revision::Deleted(version) => (*version, true, None),
+        }
+    }
+}
+
+/// How much history sequenced MutableData keeps for each entry.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum HistoryPolicy {
+    /// Keep every superseded revision.
+    KeepAll,
+    /// Keep only this many of the most recent superseded revisions of each entry. Must be at
+    /// least one.
+    KeepLast(u64),
+}
+
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+struct History {
+    policy: HistoryPolicy,
+    // Superseded revisions of each entry, oldest first.
+    revisions: BTreeMap<Vec<u8>, Vec<Revision>>,
+    // The oldest entry version of each entry whose value is still known, for entries whose earlier
+    // revisions were pruned or predate the history.
+    known_from: BTreeMap<Vec<u8>, u64>,
+}
+
+impl History {
+    fn record(&mut self, key: Vec<u8>, superseded: Vec<Revision>) {
+        let revisions = self.revisions.entry(key.clone()).or_default();
+        revisions.extend(superseded);
+        if let HistoryPolicy::KeepLast(count) = self.policy {
+            let excess = revisions.len().saturating_sub(count as usize);
+            if excess > 0 {
+                let _ = revisions.drain(..excess);
+                let oldest = revisions[0].version();
+                let _ = self.known_from.insert(key, oldest);
+            }
+        }
+    }
+
+    // Returns whether the value of the entry at `key` is known at entry `version`.
+    fn is_known_at(&self, key: &[u8], version: u64) -> bool {
+        self.known_from
+            .get(key)
+            .is_none_or(|known_from| *known_from <= version)
+    }
+}
+
+/// What a replica adopted from another when merging them.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MergeReport {
+    /// Keys of the entries adopted from the other replica.
+    pub entries: BTreeSet<Vec<u8>>,
+    /// Users whose permissions were added, changed or removed.
+    pub permissions: BTreeSet<PublicKey>,
+    /// Whether the other replica's owners were adopted.
+    pub owners: bool,
+}
+
+impl MergeReport {
+    /// Returns true if nothing was adopted.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty() && self.permissions.is_empty() && !self.owners
+    }
+}
+
 /// Set of user permissions.
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord, Serialize, Deserialize, Default)]
 pub struct PermissionSet {
@@ -217,9 +318,16 @@ macro_rules! impl_mutable_data {
                 self.version
             }
 
-            /// Returns the owner key.
-            pub fn owner(&self) -> &PublicKey {
-                &self.owner
+            /// Returns the version of the whole content, which increases with every change to the
+            /// data, including its entries. A merge takes the greater of the two replicas' content
+            /// versions.
+            pub fn content_version(&self) -> u64 {
+                self.content_version
+            }
+
+            /// Returns the owners.
+            pub fn owners(&self) -> &Owners {
+                &self.owners
             }
 
             /// Returns all the keys in the data.
@@ -227,15 +335,11 @@ macro_rules! impl_mutable_data {
                 self.data.keys().cloned().collect()
             }
 
-            /// Returns the shell of this MutableData (the fields without the data).
-            pub fn shell(&self) -> Self {
-                Self {
-                    address: self.address.clone(),
-                    data: BTreeMap::new(),
-                    permissions: self.permissions.clone(),
-                    version: self.version,
-                    owner: self.owner,
-                }
+            /// Returns the page of keys at `cursor`.
+            ///
+            /// Returns `Err(Error::InvalidOperation)` if the cursor's limit is zero.
+            pub fn keys_page(&self, cursor: &Cursor) -> Result<Page<BTreeSet<Vec<u8>>>> {
+                Ok(page_of(&self.data, cursor)?.map(|entries| entries.into_keys().collect()))
             }
 
             /// Gets a complete list of permissions.
@@ -248,23 +352,35 @@ macro_rules! impl_mutable_data {
                 self.permissions.get(&user).ok_or(Error::NoSuchKey)
             }
 
-            /// Checks if the provided user is an owner.
+            /// Checks if the provided user is an owner whose approval alone is enough. See
+            /// `Owners::check_is_owner`.
             ///
             /// Returns `Ok(())` on success and `Err(Error::AccessDenied)` if the user is not an
             /// owner.
             pub fn check_is_owner(&self, requester: PublicKey) -> Result<()> {
-                if self.owner == requester {
-                    Ok(())
-                } else {
-                    Err(Error::AccessDenied)
-                }
+                self.owners.check_is_owner(requester)
+            }
+
+            /// Checks that `signatures` over `data` carry enough of the owners' approval. See
+            /// `Owners::check_approval`.
+            pub fn check_owners_approval(
+                &self,
+                data: &[u8],
+                signatures: &BTreeMap<PublicKey, Signature>,
+            ) -> Result<()> {
+                self.owners.check_approval(data, signatures)
             }
 
             /// Checks permissions for given `action` for the provided user.
             ///
+            /// A single requester only has an owner's rights if its approval alone is enough (see
+            /// `check_is_owner`). With a threshold of more than one owner, no single key does, so
+            /// entries can then only be mutated by users granted permissions, and owner approval
+            /// is checked separately with `check_owners_approval`.
+            ///
             /// Returns `Err(Error::AccessDenied)` if the permission check has failed.
             pub fn check_permissions(&self, action: Action, requester: PublicKey) -> Result<()> {
-                if self.owner == requester {
+                if self.check_is_owner(requester).is_ok() {
                     Ok(())
                 } else {
                     let permissions = self
@@ -294,6 +410,7 @@ macro_rules! impl_mutable_data {
 
                 let _prev = self.permissions.insert(user, permissions);
                 self.version = version;
+                self.content_version += 1;
 
                 Ok(())
             }
@@ -312,6 +429,7 @@ macro_rules! impl_mutable_data {
 
                 let _ = self.permissions.remove(&user);
                 self.version = version;
+                self.content_version += 1;
 
                 Ok(())
             }
@@ -331,40 +449,47 @@ macro_rules! impl_mutable_data {
 
                 let _ = self.permissions.remove(&user);
                 self.version = version;
+                self.content_version += 1;
 
                 true
             }
 
-            /// Changes the owner.
+            /// Changes the owners.
             ///
             /// Requires the new `version` of the MutableData fields. If it does not match the
             /// current version + 1, an error will be returned.
-            pub fn change_owner(&mut self, new_owner: PublicKey, version: u64) -> Result<()> {
+            pub fn change_owner(
+                &mut self,
+                new_owners: impl Into<Owners>,
+                version: u64,
+            ) -> Result<()> {
                 if version != self.version + 1 {
                     return Err(Error::InvalidSuccessor(self.version));
                 }
 
-                self.owner = new_owner;
+                self.owners = new_owners.into();
                 self.version = version;
+                self.content_version += 1;
 
                 Ok(())
             }
 
-            /// Changes the owner without performing any validation.
+            /// Changes the owners without performing any validation.
             ///
             /// Requires the new `version` of the MutableData fields. If it does not match the
             /// current version + 1, an error will be returned.
             pub fn change_owner_without_validation(
                 &mut self,
-                new_owner: PublicKey,
+                new_owners: impl Into<Owners>,
                 version: u64,
             ) -> bool {
                 if version <= self.version {
                     return false;
                 }
 
-                self.owner = new_owner;
+                self.owners = new_owners.into();
                 self.version = version;
+                self.content_version += 1;
 
                 true
             }
@@ -376,6 +501,39 @@ macro_rules! impl_mutable_data {
                     None => false,
                 }
             }
+
+            // Adopts the permissions and owners of `other` if its fields version is higher, or if
+            // the versions are equal and its permissions and owners order higher.
+            fn merge_fields(&mut self, other: &Self, report: &mut MergeReport) {
+                let adopt = match other.version.cmp(&self.version) {
+                    Ordering::Greater => true,
+                    Ordering::Less => false,
+                    Ordering::Equal => {
+                        (&other.permissions, &other.owners) > (&self.permissions, &self.owners)
+                    }
+                };
+                if !adopt {
+                    return;
+                }
+
+                report.permissions.extend(
+                    self.permissions
+                        .keys()
+                        .chain(other.permissions.keys())
+                        .filter(|user| self.permissions.get(user) != other.permissions.get(user))
+                        .cloned(),
+                );
+                report.owners = self.owners != other.owners;
+                self.permissions = other.permissions.clone();
+                self.owners = other.owners.clone();
+                self.version = other.version;
+            }
+
+            // Takes the greater of the two content versions, whichever way round the replicas are
+            // merged.
+            fn merge_content_version(&mut self, other: &Self) {
+                self.content_version = cmp::max(self.content_version, other.content_version);
+            }
         }
     };
 }
@@ -385,13 +543,14 @@ impl_mutable_data!(UnseqData);
 
 impl UnseqData {
     /// Creates a new unsequenced MutableData.
-    pub fn new(name: XorName, tag: u64, owner: PublicKey) -> Self {
+    pub fn new(name: XorName, tag: u64, owners: impl Into<Owners>) -> Self {
         Self {
             address: Address::Unseq { name, tag },
             data: Default::default(),
             permissions: Default::default(),
             version: 0,
-            owner,
+            content_version: 0,
+            owners: owners.into(),
         }
     }
 
@@ -401,14 +560,27 @@ impl UnseqData {
         tag: u64,
         data: UnseqEntries,
         permissions: BTreeMap<PublicKey, PermissionSet>,
-        owner: PublicKey,
+        owners: impl Into<Owners>,
     ) -> Self {
         Self {
             address: Address::Unseq { name, tag },
             data,
             permissions,
             version: 0,
-            owner,
+            content_version: 0,
+            owners: owners.into(),
+        }
+    }
+
+    /// Returns the shell of this MutableData (the fields without the data).
+    pub fn shell(&self) -> Self {
+        Self {
+            address: self.address,
+            data: BTreeMap::new(),
+            permissions: self.permissions.clone(),
+            version: self.version,
+            content_version: self.content_version,
+            owners: self.owners.clone(),
         }
     }
 
@@ -427,24 +599,46 @@ impl UnseqData {
         &self.data
     }
 
+    /// Returns the page of entries at `cursor`.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the cursor's limit is zero.
+    pub fn entries_page(&self, cursor: &Cursor) -> Result<Page<UnseqEntries>> {
+        page_of(&self.data, cursor)
+    }
+
+    /// Returns the page of values at `cursor`, in the order of their keys.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the cursor's limit is zero.
+    pub fn values_page(&self, cursor: &Cursor) -> Result<Page<Vec<Vec<u8>>>> {
+        Ok(self
+            .entries_page(cursor)?
+            .map(|entries| entries.into_values().collect()))
+    }
+
     /// Removes and returns all entries.
     pub fn take_entries(&mut self) -> UnseqEntries {
-        mem::replace(&mut self.data, BTreeMap::new())
+        self.content_version += 1;
+        mem::take(&mut self.data)
     }
 
     /// Mutates entries based on `actions` for the provided user.
     ///
+    /// The conditions of conditional actions are checked against the current entries, and all the
+    /// actions are applied only if every one of them is valid. An insert-or-update counts as an
+    /// insert or an update for the permissions check depending on whether the entry exists.
+    ///
     /// Returns `Err(InvalidEntryActions)` if the mutation parameters are invalid.
     pub fn mutate_entries(
         &mut self,
         actions: UnseqEntryActions,
         requester: PublicKey,
     ) -> Result<()> {
+        // Updates and deletes are keyed on the value expected to be current, if any.
         let (insert, update, delete) = actions.actions.into_iter().fold(
             (
                 BTreeMap::<Vec<u8>, Vec<u8>>::new(),
-                BTreeMap::<Vec<u8>, Vec<u8>>::new(),
-                BTreeSet::<Vec<u8>>::new(),
+                BTreeMap::<Vec<u8>, (Option<Vec<u8>>, Vec<u8>)>::new(),
+                BTreeMap::<Vec<u8>, Option<Vec<u8>>>::new(),
             ),
             |(mut insert, mut update, mut delete), (key, item)| {
                 match item {
@@ -452,17 +646,30 @@ impl UnseqData {
                         let _ = insert.insert(key, value);
                     }
                     UnseqEntryAction::Update(value) => {
-                        let _ = update.insert(key, value);
+                        let _ = update.insert(key, (None, value));
                     }
                     UnseqEntryAction::Del => {
-                        let _ = delete.insert(key);
+                        let _ = delete.insert(key, None);
+                    }
+                    UnseqEntryAction::UpdateIfEquals { expected, value } => {
+                        let _ = update.insert(key, (Some(expected), value));
+                    }
+                    UnseqEntryAction::DelIfEquals(expected) => {
+                        let _ = delete.insert(key, Some(expected));
+                    }
+                    UnseqEntryAction::Upsert(value) => {
+                        if self.data.contains_key(&key) {
+                            let _ = update.insert(key, (None, value));
+                        } else {
+                            let _ = insert.insert(key, value);
+                        }
                     }
                 };
                 (insert, update, delete)
             },
         );
 
-        if *self.owner() != requester
+        if self.check_is_owner(requester).is_err()
             && ((!insert.is_empty() && !self.is_action_allowed(&requester, Action::Insert))
                 || (!update.is_empty() && !self.is_action_allowed(&requester, Action::Update))
                 || (!delete.is_empty() && !self.is_action_allowed(&requester, Action::Delete)))
@@ -484,10 +691,14 @@ impl UnseqData {
             }
         }
 
-        for (key, val) in update {
+        for (key, (expected, val)) in update {
             match new_data.entry(key) {
                 Entry::Occupied(mut entry) => {
-                    let _ = entry.insert(val);
+                    if expected.is_none_or(|expected| *entry.get() == expected) {
+                        let _ = entry.insert(val);
+                    } else {
+                        let _ = errors.insert(entry.key().clone(), EntryError::ValueMismatch);
+                    }
                 }
                 Entry::Vacant(entry) => {
                     let _ = errors.insert(entry.key().clone(), EntryError::NoSuchEntry);
@@ -495,10 +706,14 @@ impl UnseqData {
             }
         }
 
-        for key in delete {
-            match new_data.entry(key.clone()) {
-                Entry::Occupied(_) => {
-                    let _ = new_data.remove(&key);
+        for (key, expected) in delete {
+            match new_data.entry(key) {
+                Entry::Occupied(entry) => {
+                    if expected.is_none_or(|expected| *entry.get() == expected) {
+                        let _ = entry.remove();
+                    } else {
+                        let _ = errors.insert(entry.key().clone(), EntryError::ValueMismatch);
+                    }
                 }
                 Entry::Vacant(entry) => {
                     let _ = errors.insert(entry.key().clone(), EntryError::NoSuchEntry);
@@ -511,21 +726,55 @@ impl UnseqData {
         }
 
         let _old_data = mem::replace(&mut self.data, new_data);
+        self.content_version += 1;
 
         Ok(())
     }
+
+    /// Merges `other`, a diverged replica of this data, into this one. The result is the same
+    /// whichever way round two replicas are merged.
+    ///
+    /// Permissions and owners are adopted together from the replica with the higher fields
+    /// version. Unsequenced entries carry no versions, so an entry present in only one replica is
+    /// kept, and where the replicas' values differ the greater value in byte order wins. As
+    /// deletions leave no trace, an entry deleted in only one replica is restored.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if `other` has a different address.
+    pub fn merge(&mut self, other: &Self) -> Result<MergeReport> {
+        if self.address != other.address {
+            return Err(Error::InvalidOperation);
+        }
+        let mut report = MergeReport::default();
+
+        self.merge_fields(other, &mut report);
+        self.merge_content_version(other);
+        for (key, value) in &other.data {
+            match self.data.get(key) {
+                Some(current) if current >= value => (),
+                _ => {
+                    let _ = self.data.insert(key.clone(), value.clone());
+                    let _ = report.entries.insert(key.clone());
+                }
+            }
+        }
+
+        Ok(report)
+    }
 }
 
 /// Implements functions for sequenced MutableData.
 impl SeqData {
     /// Creates a new sequenced MutableData.
-    pub fn new(name: XorName, tag: u64, owner: PublicKey) -> Self {
+    pub fn new(name: XorName, tag: u64, owners: impl Into<Owners>) -> Self {
         Self {
             address: Address::Seq { name, tag },
             data: Default::default(),
             permissions: Default::default(),
             version: 0,
-            owner,
+            content_version: 0,
+            owners: owners.into(),
+            history: None,
+            tombstones: BTreeMap::new(),
         }
     }
 
@@ -535,14 +784,56 @@ impl SeqData {
         tag: u64,
         data: SeqEntries,
         permissions: BTreeMap<PublicKey, PermissionSet>,
-        owner: PublicKey,
+        owners: impl Into<Owners>,
     ) -> Self {
         Self {
             address: Address::Seq { name, tag },
             data,
             permissions,
             version: 0,
-            owner,
+            content_version: 0,
+            owners: owners.into(),
+            history: None,
+            tombstones: BTreeMap::new(),
+        }
+    }
+
+    /// Enables history: superseded values and deletions of each entry are kept, subject to
+    /// `policy`. Revisions of the current entries from before history was enabled aren't known.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` for `HistoryPolicy::KeepLast(0)`.
+    pub fn with_history(mut self, policy: HistoryPolicy) -> Result<Self> {
+        if policy == HistoryPolicy::KeepLast(0) {
+            return Err(Error::InvalidOperation);
+        }
+        let known_from = self
+            .entries_with_tombstones()
+            .into_iter()
+            .map(|(key, revision)| (key, revision.version()))
+            .collect();
+        self.history = Some(History {
+            policy,
+            revisions: BTreeMap::new(),
+            known_from,
+        });
+        Ok(self)
+    }
+
+    /// Returns the shell of this MutableData (the fields without the data or its history).
+    pub fn shell(&self) -> Self {
+        Self {
+            address: self.address,
+            data: BTreeMap::new(),
+            permissions: self.permissions.clone(),
+            version: self.version,
+            content_version: self.content_version,
+            owners: self.owners.clone(),
+            history: self.history.as_ref().map(|history| History {
+                policy: history.policy,
+                revisions: BTreeMap::new(),
+                known_from: BTreeMap::new(),
+            }),
+            tombstones: BTreeMap::new(),
         }
     }
 
@@ -551,6 +842,54 @@ impl SeqData {
         self.data.get(key)
     }
 
+    /// Returns the value the entry at `key` held at entry `version`, or `None` if it didn't exist
+    /// or had been deleted by then.
+    ///
+    /// The current value and tombstones answer for versions from theirs on. Older versions need
+    /// the history: returns `Err(Error::HistoryNotKept)` if history isn't enabled, or the revisions
+    /// needed were pruned or predate enabling it.
+    pub fn get_at(&self, key: &[u8], version: u64) -> Result<Option<&SeqValue>> {
+        match (self.data.get(key), self.tombstones.get(key)) {
+            (Some(value), _) if value.version <= version => return Ok(Some(value)),
+            (None, Some(deleted_version)) if *deleted_version <= version => return Ok(None),
+            // The entry has never existed.
+            (None, None) => return Ok(None),
+            _ => (),
+        }
+        let history = self.history.as_ref().ok_or(Error::HistoryNotKept)?;
+        if !history.is_known_at(key, version) {
+            return Err(Error::HistoryNotKept);
+        }
+        let revision = history.revisions.get(key).and_then(|revisions| {
+            revisions
+                .iter()
+                .rev()
+                .find(|revision| revision.version() <= version)
+        });
+        match revision {
+            Some(Revision::Value(value)) => Ok(Some(value)),
+            Some(Revision::Deleted(_)) | None => Ok(None),
+        }
+    }
+
+    /// Returns the history policy, or `None` if history isn't enabled.
+    pub fn history_policy(&self) -> Option<HistoryPolicy> {
+        self.history.as_ref().map(|history| history.policy)
+    }
+
+    /// Returns the known revisions of the entry at `key`, oldest first, ending with its current
+    /// value if it has one.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if history isn't enabled.
+    pub fn history(&self, key: &[u8]) -> Result<Vec<Revision>> {
+        let history = self.history.as_ref().ok_or(Error::InvalidOperation)?;
+        let mut revisions = history.revisions.get(key).cloned().unwrap_or_default();
+        if let Some(value) = self.data.get(key) {
+            revisions.push(Revision::Value(value.clone()));
+        }
+        Ok(revisions)
+    }
+
     /// Returns values of all entries
     pub fn values(&self) -> Vec<SeqValue> {
         self.data.values().cloned().collect()
@@ -561,13 +900,66 @@ impl SeqData {
         &self.data
     }
 
+    /// Returns the deleted keys, with the versions at which they were deleted.
+    pub fn tombstones(&self) -> &BTreeMap<Vec<u8>, u64> {
+        &self.tombstones
+    }
+
+    /// Returns all the keys, including those of deleted entries.
+    pub fn keys_with_tombstones(&self) -> BTreeSet<Vec<u8>> {
+        self.data
+            .keys()
+            .chain(self.tombstones.keys())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the values of all entries, with a `Revision::Deleted` in place of each deleted
+    /// entry, in the order of their keys.
+    pub fn values_with_tombstones(&self) -> Vec<Revision> {
+        self.entries_with_tombstones().into_values().collect()
+    }
+
+    /// Returns all entries, with a `Revision::Deleted` for each deleted entry.
+    pub fn entries_with_tombstones(&self) -> BTreeMap<Vec<u8>, Revision> {
+        self.data
+            .iter()
+            .map(|(key, value)| (key.clone(), Revision::Value(value.clone())))
+            .chain(
+                self.tombstones
+                    .iter()
+                    .map(|(key, version)| (key.clone(), Revision::Deleted(*version))),
+            )
+            .collect()
+    }
+
+    /// Returns the page of entries at `cursor`.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the cursor's limit is zero.
+    pub fn entries_page(&self, cursor: &Cursor) -> Result<Page<SeqEntries>> {
+        page_of(&self.data, cursor)
+    }
+
+    /// Returns the page of values at `cursor`, in the order of their keys.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the cursor's limit is zero.
+    pub fn values_page(&self, cursor: &Cursor) -> Result<Page<Vec<SeqValue>>> {
+        Ok(self
+            .entries_page(cursor)?
+            .map(|entries| entries.into_values().collect()))
+    }
+
     /// Removes and returns all entries
     pub fn take_entries(&mut self) -> SeqEntries {
-        mem::replace(&mut self.data, BTreeMap::new())
+        self.content_version += 1;
+        mem::take(&mut self.data)
     }
 
     /// Mutates entries (key + value pairs) in bulk.
     ///
+    /// A deleted entry leaves a tombstone keeping its version, so re-inserting it needs the next
+    /// version after the tombstone's.
+    ///
     /// Returns `Err(InvalidEntryActions)` if the mutation parameters are invalid.
     pub fn mutate_entries(&mut self, actions: SeqEntryActions, requester: PublicKey) -> Result<()> {
         // Deconstruct actions into inserts, updates, and deletes
@@ -589,7 +981,7 @@ impl SeqData {
             },
         );
 
-        if *self.owner() != requester
+        if self.check_is_owner(requester).is_err()
             && ((!insert.is_empty() && !self.is_action_allowed(&requester, Action::Insert))
                 || (!update.is_empty() && !self.is_action_allowed(&requester, Action::Update))
                 || (!delete.is_empty() && !self.is_action_allowed(&requester, Action::Delete)))
@@ -598,7 +990,9 @@ impl SeqData {
         }
 
         let mut new_data = self.data.clone();
+        let mut new_tombstones = self.tombstones.clone();
         let mut errors = BTreeMap::new();
+        let mut superseded = BTreeMap::new();
 
         for (key, val) in insert {
             match new_data.entry(key) {
@@ -608,9 +1002,18 @@ impl SeqData {
                         EntryError::EntryExists(entry.get().version as u8),
                     );
                 }
-                Entry::Vacant(entry) => {
-                    let _ = entry.insert(val);
-                }
+                Entry::Vacant(entry) => match new_tombstones.get(entry.key()) {
+                    Some(&deleted_version)
+                        if deleted_version.checked_add(1) != Some(val.version) =>
+                    {
+                        let _ = errors
+                            .insert(entry.key().clone(), EntryError::Deleted(deleted_version));
+                    }
+                    _ => {
+                        let _ = new_tombstones.remove(entry.key());
+                        let _ = entry.insert(val);
+                    }
+                },
             }
         }
 
@@ -619,7 +1022,9 @@ impl SeqData {
                 Entry::Occupied(mut entry) => {
                     let current_version = entry.get().version;
                     if val.version == current_version + 1 {
-                        let _ = entry.insert(val);
+                        let old_value = entry.insert(val);
+                        let _ = superseded
+                            .insert(entry.key().clone(), vec![Revision::Value(old_value)]);
                     } else {
                         let _ = errors.insert(
                             entry.key().clone(),
@@ -638,7 +1043,12 @@ impl SeqData {
                 Entry::Occupied(entry) => {
                     let current_version = entry.get().version;
                     if version == current_version + 1 {
-                        let _ = new_data.remove(&key);
+                        let old_value = entry.remove();
+                        let _ = new_tombstones.insert(key.clone(), version);
+                        let _ = superseded.insert(
+                            key,
+                            vec![Revision::Value(old_value), Revision::Deleted(version)],
+                        );
                     } else {
                         let _ = errors.insert(
                             entry.key().clone(),
@@ -657,9 +1067,85 @@ impl SeqData {
         }
 
         let _old_data = mem::replace(&mut self.data, new_data);
+        self.content_version += 1;
+        self.tombstones = new_tombstones;
+        if let Some(history) = self.history.as_mut() {
+            for (key, revisions) in superseded {
+                history.record(key, revisions);
+            }
+        }
 
         Ok(())
     }
+
+    /// Merges `other`, a diverged replica of this data, into this one. The entries, tombstones,
+    /// permissions and owners are the same whichever way round two replicas are merged, but the
+    /// history isn't: each replica keeps its own, and `other`'s is not merged.
+    ///
+    /// Permissions and owners are adopted together from the replica with the higher fields
+    /// version. For each entry, the higher entry version wins, counting tombstones. At equal
+    /// versions a tombstone wins over a value, and otherwise the greater value in byte order wins.
+    /// Values superseded here are recorded in this replica's history, if enabled, and revisions
+    /// only `other` saw are treated as not kept by `get_at`.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if `other` has a different address.
+    pub fn merge(&mut self, other: &Self) -> Result<MergeReport> {
+        if self.address != other.address {
+            return Err(Error::InvalidOperation);
+        }
+        let mut report = MergeReport::default();
+
+        self.merge_fields(other, &mut report);
+        self.merge_content_version(other);
+        for (key, theirs) in other.entries_with_tombstones() {
+            let ours = self
+                .data
+                .get(&key)
+                .cloned()
+                .map(Revision::Value)
+                .or_else(|| {
+                    self.tombstones
+                        .get(&key)
+                        .map(|version| Revision::Deleted(*version))
+                });
+            if let Some(ours) = &ours {
+                if ours.merge_order() >= theirs.merge_order() {
+                    continue;
+                }
+            }
+
+            // Revisions between ours and theirs weren't seen by this replica.
+            let skipped = match &ours {
+                Some(ours) => theirs.version() > ours.version().saturating_add(1),
+                None => theirs.version() > 0,
+            };
+            let version = theirs.version();
+            let mut superseded = Vec::new();
+            if let Some(Revision::Value(value)) = ours {
+                superseded.push(Revision::Value(value));
+            }
+            match theirs {
+                Revision::Value(value) => {
+                    let _ = self.tombstones.remove(&key);
+                    let _ = self.data.insert(key.clone(), value);
+                }
+                Revision::Deleted(version) => {
+                    let _ = self.data.remove(&key);
+                    let _ = self.tombstones.insert(key.clone(), version);
+                    superseded.push(Revision::Deleted(version));
+                }
+            }
+            if let Some(history) = self.history.as_mut() {
+                if skipped {
+                    let _ = history.known_from.insert(key.clone(), version);
+                }
+                history.record(key.clone(), superseded);
+            }
+            let _ = report.entries.insert(key);
+        }
+
+        Ok(report)
+    }
 }
 
 /// Kind of a MutableData.
@@ -814,6 +1300,21 @@ impl Data {
         }
     }
 
+    /// Returns the version of the whole content. See `SeqData::content_version`.
+    pub fn content_version(&self) -> u64 {
+        match self {
+            Data::Seq(data) => data.content_version(),
+            Data::Unseq(data) => data.content_version(),
+        }
+    }
+
+    /// Returns true if this data has a greater content version than `known_version`, i.e. if a
+    /// copy of it at `known_version` is stale. Changes to the entries count as well as to the
+    /// permissions or owners.
+    pub fn is_newer_than(&self, known_version: u64) -> bool {
+        self.content_version() > known_version
+    }
+
     /// Returns all the keys in the data.
     pub fn keys(&self) -> BTreeSet<Vec<u8>> {
         match self {
@@ -822,6 +1323,36 @@ impl Data {
         }
     }
 
+    /// Returns the page of entries at `cursor`.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the cursor's limit is zero.
+    pub fn entries_page(&self, cursor: &Cursor) -> Result<Page<Entries>> {
+        Ok(match self {
+            Data::Seq(data) => data.entries_page(cursor)?.map(Entries::Seq),
+            Data::Unseq(data) => data.entries_page(cursor)?.map(Entries::Unseq),
+        })
+    }
+
+    /// Returns the page of keys at `cursor`.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the cursor's limit is zero.
+    pub fn keys_page(&self, cursor: &Cursor) -> Result<Page<BTreeSet<Vec<u8>>>> {
+        match self {
+            Data::Seq(data) => data.keys_page(cursor),
+            Data::Unseq(data) => data.keys_page(cursor),
+        }
+    }
+
+    /// Returns the page of values at `cursor`, in the order of their keys.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the cursor's limit is zero.
+    pub fn values_page(&self, cursor: &Cursor) -> Result<Page<Values>> {
+        Ok(match self {
+            Data::Seq(data) => data.values_page(cursor)?.map(Values::Seq),
+            Data::Unseq(data) => data.values_page(cursor)?.map(Values::Unseq),
+        })
+    }
+
     /// Returns the shell of the data.
     pub fn shell(&self) -> Self {
         match self {
@@ -883,11 +1414,23 @@ impl Data {
         }
     }
 
-    /// Returns the owner key.
-    pub fn owner(&self) -> PublicKey {
+    /// Checks that `signatures` over `data` carry enough of the owners' approval.
+    pub fn check_owners_approval(
+        &self,
+        data: &[u8],
+        signatures: &BTreeMap<PublicKey, Signature>,
+    ) -> Result<()> {
         match self {
-            Data::Seq(data) => data.owner,
-            Data::Unseq(data) => data.owner,
+            Data::Seq(mdata) => mdata.check_owners_approval(data, signatures),
+            Data::Unseq(mdata) => mdata.check_owners_approval(data, signatures),
+        }
+    }
+
+    /// Returns the owners.
+    pub fn owners(&self) -> &Owners {
+        match self {
+            Data::Seq(data) => data.owners(),
+            Data::Unseq(data) => data.owners(),
         }
     }
 
@@ -908,6 +1451,232 @@ impl Data {
 
         Err(Error::InvalidOperation)
     }
+
+    /// Returns what changed between the `old` and `new` snapshots of the same MutableData.
+    ///
+    /// A sequenced entry deleted and re-inserted is only told apart from one updated if the
+    /// history of `new` kept the deletion.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the snapshots have different addresses.
+    pub fn diff(old: &Self, new: &Self) -> Result<Diff> {
+        if old.address() != new.address() {
+            return Err(Error::InvalidOperation);
+        }
+
+        let (added, updated, removed, tombstones) = match (old, new) {
+            (Data::Seq(old), Data::Seq(new)) => {
+                let (mut added, mut updated, mut removed) =
+                    diff_entries(old.entries(), new.entries());
+                let mut tombstones: BTreeMap<_, _> = removed
+                    .iter()
+                    .map(|(key, value)| {
+                        let version = new
+                            .tombstones()
+                            .get(key)
+                            .copied()
+                            .unwrap_or(value.version + 1);
+                        (key.clone(), version)
+                    })
+                    .collect();
+
+                // Entries deleted and re-inserted since the old snapshot, as far as the history of
+                // the new one shows.
+                let reinserted: Vec<_> = updated
+                    .iter()
+                    .filter_map(|(key, value)| {
+                        let old_version = old.get(key)?.version;
+                        let deleted_version = new.history(key).ok()?.into_iter().find_map(
+                            |revision| match revision {
+                                Revision::Deleted(version)
+                                    if version > old_version && version < value.version =>
+                                {
+                                    Some(version)
+                                }
+                                _ => None,
+                            },
+                        )?;
+                        Some((key.clone(), deleted_version))
+                    })
+                    .collect();
+                for (key, deleted_version) in reinserted {
+                    if let (Some(old_value), Some(new_value)) =
+                        (old.get(&key), updated.remove(&key))
+                    {
+                        let _ = removed.insert(key.clone(), old_value.clone());
+                        let _ = added.insert(key.clone(), new_value);
+                        let _ = tombstones.insert(key, deleted_version);
+                    }
+                }
+
+                (added.into(), updated.into(), removed.into(), tombstones)
+            }
+            (Data::Unseq(old), Data::Unseq(new)) => {
+                let (added, updated, removed) = diff_entries(old.entries(), new.entries());
+                (
+                    added.into(),
+                    updated.into(),
+                    removed.into(),
+                    BTreeMap::new(),
+                )
+            }
+            _ => return Err(Error::InvalidOperation),
+        };
+
+        let old_permissions = old.permissions();
+        let new_permissions = new.permissions();
+        let permissions = old_permissions
+            .keys()
+            .chain(new_permissions.keys())
+            .filter(|user| old_permissions.get(user) != new_permissions.get(user))
+            .map(|user| (*user, new_permissions.get(user).cloned()))
+            .collect();
+
+        Ok(Diff {
+            address: *new.address(),
+            added,
+            updated,
+            removed,
+            tombstones,
+            permissions,
+            owners: if old.owners() != new.owners() {
+                Some(new.owners().clone())
+            } else {
+                None
+            },
+            old_version: old.version(),
+            new_version: new.version(),
+        })
+    }
+}
+
+/// The changes between two snapshots of a MutableData, as returned by `Data::diff`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diff {
+    /// Address of the data.
+    pub address: Address,
+    /// Entries only in the new snapshot, and sequenced entries re-inserted after being deleted.
+    pub added: Entries,
+    /// Entries whose values changed, with their new values.
+    pub updated: Entries,
+    /// Entries only in the old snapshot, and sequenced entries deleted before being re-inserted,
+    /// with their old values.
+    pub removed: Entries,
+    /// Versions at which the sequenced entries in `removed` were deleted.
+    pub tombstones: BTreeMap<Vec<u8>, u64>,
+    /// Users whose permissions changed, with their new permissions or `None` if they were removed.
+    pub permissions: BTreeMap<PublicKey, Option<PermissionSet>>,
+    /// The new owners, if they changed.
+    pub owners: Option<Owners>,
+    /// Version of the MutableData fields in the old snapshot.
+    pub old_version: u64,
+    /// Version of the MutableData fields in the new snapshot.
+    pub new_version: u64,
+}
+
+impl Diff {
+    /// Returns true if nothing changed.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.updated.is_empty()
+            && self.removed.is_empty()
+            && self.permissions.is_empty()
+            && self.owners.is_none()
+            && self.old_version == self.new_version
+    }
+
+    /// Returns the entry actions that transform the entries of the old snapshot into the new, in
+    /// the order they must be applied. Entries which were deleted and re-inserted are inserted
+    /// again by a second set of actions, after the first deletes them.
+    ///
+    /// Sequenced actions carry the new values' versions, and deletes the versions in
+    /// `tombstones`, so they only apply if each entry changed at most once between the snapshots,
+    /// counting a deletion and re-insertion as one change. Updated or removed entries of a
+    /// different kind from the added ones are ignored.
+    pub fn entry_actions(&self) -> Vec<EntryActions> {
+        let actions: Vec<EntryActions> = match &self.added {
+            Entries::Seq(added) => {
+                let mut actions = SeqEntryActions::new();
+                let mut reinserts = SeqEntryActions::new();
+                if let Entries::Seq(updated) = &self.updated {
+                    for (key, value) in updated {
+                        actions = actions.update(key.clone(), value.data.clone(), value.version);
+                    }
+                }
+                let mut removed = BTreeSet::new();
+                if let Entries::Seq(old) = &self.removed {
+                    for (key, value) in old {
+                        let version = self
+                            .tombstones
+                            .get(key)
+                            .copied()
+                            .unwrap_or(value.version + 1);
+                        actions = actions.del(key.clone(), version);
+                        let _ = removed.insert(key);
+                    }
+                }
+                for (key, value) in added {
+                    if removed.contains(key) {
+                        reinserts = reinserts.ins(key.clone(), value.data.clone(), value.version);
+                    } else {
+                        actions = actions.ins(key.clone(), value.data.clone(), value.version);
+                    }
+                }
+                vec![actions.into(), reinserts.into()]
+            }
+            Entries::Unseq(added) => {
+                let mut actions = UnseqEntryActions::new();
+                for (key, value) in added {
+                    actions = actions.ins(key.clone(), value.clone());
+                }
+                if let Entries::Unseq(updated) = &self.updated {
+                    for (key, value) in updated {
+                        actions = actions.update(key.clone(), value.clone());
+                    }
+                }
+                if let Entries::Unseq(removed) = &self.removed {
+                    for key in removed.keys() {
+                        actions = actions.del(key.clone());
+                    }
+                }
+                vec![actions.into()]
+            }
+        };
+        actions
+            .into_iter()
+            .filter(|actions| !actions.is_empty())
+            .collect()
+    }
+}
+
+// Splits the differences between `old` and `new` into the added, updated and removed entries.
+#[allow(clippy::type_complexity)]
+fn diff_entries<V: Clone + PartialEq>(
+    old: &BTreeMap<Vec<u8>, V>,
+    new: &BTreeMap<Vec<u8>, V>,
+) -> (
+    BTreeMap<Vec<u8>, V>,
+    BTreeMap<Vec<u8>, V>,
+    BTreeMap<Vec<u8>, V>,
+) {
+    let mut added = BTreeMap::new();
+    let mut updated = BTreeMap::new();
+    for (key, value) in new {
+        match old.get(key) {
+            None => {
+                let _ = added.insert(key.clone(), value.clone());
+            }
+            Some(old_value) if old_value != value => {
+                let _ = updated.insert(key.clone(), value.clone());
+            }
+            Some(_) => (),
+        }
+    }
+    let removed = old
+        .iter()
+        .filter(|(key, _)| !new.contains_key(*key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    (added, updated, removed)
 }
 
 impl From<SeqData> for Data {
@@ -962,6 +1731,17 @@ pub enum UnseqEntryAction {
     Update(Vec<u8>),
     /// Deletes an entry.
     Del,
+    /// Updates an entry with a new value if its current value equals `expected`.
+    UpdateIfEquals {
+        /// Value the entry is expected to hold.
+        expected: Vec<u8>,
+        /// New value.
+        value: Vec<u8>,
+    },
+    /// Deletes an entry if its current value equals the given one.
+    DelIfEquals(Vec<u8>),
+    /// Inserts a new entry, or updates the entry with a new value if it exists.
+    Upsert(Vec<u8>),
 }
 
 /// Sequenced Entry Actions for given entry keys.
@@ -1086,6 +1866,32 @@ impl UnseqEntryActions {
         self
     }
 
+    /// Update existing key-value pair if its value equals `expected`
+    pub fn update_if_equals(mut self, key: Vec<u8>, expected: Vec<u8>, content: Vec<u8>) -> Self {
+        let _ = self.actions.insert(
+            key,
+            UnseqEntryAction::UpdateIfEquals {
+                expected,
+                value: content,
+            },
+        );
+        self
+    }
+
+    /// Delete existing key if its value equals `expected`
+    pub fn del_if_equals(mut self, key: Vec<u8>, expected: Vec<u8>) -> Self {
+        let _ = self
+            .actions
+            .insert(key, UnseqEntryAction::DelIfEquals(expected));
+        self
+    }
+
+    /// Insert a new key-value pair, or update it if the key exists
+    pub fn upsert(mut self, key: Vec<u8>, content: Vec<u8>) -> Self {
+        let _ = self.actions.insert(key, UnseqEntryAction::Upsert(content));
+        self
+    }
+
     /// Adds a UnseqEntryAction to the list of actions, replacing it if it is already present
     pub fn add_action(&mut self, key: Vec<u8>, action: UnseqEntryAction) {
         let _ = self.actions.insert(key, action);
@@ -1121,6 +1927,14 @@ impl EntryActions {
             EntryActions::Unseq(_) => Kind::Unseq,
         }
     }
+
+    /// Returns true if there are no actions.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            EntryActions::Seq(actions) => actions.actions.is_empty(),
+            EntryActions::Unseq(actions) => actions.actions.is_empty(),
+        }
+    }
 }
 
 impl From<SeqEntryActions> for EntryActions {
@@ -1149,6 +1963,16 @@ pub enum Entries {
     Unseq(UnseqEntries),
 }
 
+impl Entries {
+    /// Returns true if there are no entries.
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Entries::Seq(entries) => entries.is_empty(),
+            Entries::Unseq(entries) => entries.is_empty(),
+        }
+    }
+}
+
 impl From<SeqEntries> for Entries {
     fn from(entries: SeqEntries) -> Self {
         Entries::Seq(entries)
@@ -1161,9 +1985,80 @@ impl From<UnseqEntries> for Entries {
     }
 }
 
+/// Position in the keys of MutableData from which to list a page of entries, keys or values.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub struct Cursor {
+    /// Key at which the page starts (inclusive), or `None` to start at the first key.
+    pub start: Option<Vec<u8>>,
+    /// Maximum number of items in the page. Must be non-zero, and is clamped to
+    /// [`MAX_PAGE_LIMIT`](constant.MAX_PAGE_LIMIT.html).
+    pub limit: u64,
+}
+
+/// A page of entries, keys or values of MutableData, in key order.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub struct Page<T> {
+    /// Items in the page.
+    pub items: T,
+    /// Key at which the next page starts, or `None` if this is the last page.
+    pub next: Option<Vec<u8>>,
+}
+
+impl<T> Page<T> {
+    /// Returns true if there are no more pages after this one.
+    pub fn is_last(&self) -> bool {
+        self.next.is_none()
+    }
+
+    /// Returns the cursor for the next page, holding at most `limit` items.
+    pub fn next_cursor(&self, limit: u64) -> Option<Cursor> {
+        self.next.clone().map(|start| Cursor {
+            start: Some(start),
+            limit,
+        })
+    }
+
+    /// Converts the items of the page, keeping its continuation.
+    pub fn map<U, F: FnOnce(T) -> U>(self, f: F) -> Page<U> {
+        Page {
+            items: f(self.items),
+            next: self.next,
+        }
+    }
+}
+
+fn page_of<V: Clone>(
+    data: &BTreeMap<Vec<u8>, V>,
+    cursor: &Cursor,
+) -> Result<Page<BTreeMap<Vec<u8>, V>>> {
+    // A zero limit would give a page which continues where it started.
+    if cursor.limit == 0 {
+        return Err(Error::InvalidOperation);
+    }
+    let start = match cursor.start {
+        Some(ref key) => Bound::Included(&key[..]),
+        None => Bound::Unbounded,
+    };
+    let mut iter = data.range::<[u8], _>((start, Bound::Unbounded));
+    let items = iter
+        .by_ref()
+        .take(cmp::min(cursor.limit, MAX_PAGE_LIMIT) as usize)
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    let next = iter.next().map(|(key, _)| key.clone());
+
+    Ok(Page { items, next })
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Address, XorName};
+    use super::{
+        Action, Address, Cursor, Data, Entries, EntryError, Error, HistoryPolicy, MergeReport,
+        Owners, PermissionSet, PublicKey, Revision, SeqData, SeqEntryActions, SeqValue, Signature,
+        UnseqData, UnseqEntryActions, Values, XorName, MAX_PAGE_LIMIT,
+    };
+    use std::collections::BTreeMap;
+    use threshold_crypto::SecretKey;
     use unwrap::unwrap;
 
     #[test]
@@ -1171,7 +2066,610 @@ mod tests {
         let name = XorName(rand::random());
         let address = Address::Seq { name, tag: 15000 };
         let encoded = address.encode_to_zbase32();
-        let decoded = unwrap!(self::Address::decode_from_zbase32(&encoded));
+        let decoded = unwrap!(Address::decode_from_zbase32(&encoded));
         assert_eq!(address, decoded);
     }
+
+    #[test]
+    fn pages() {
+        let owner: PublicKey = SecretKey::random().public_key().into();
+        let entries: BTreeMap<_, _> = (0..5u8).map(|i| (vec![i], vec![i * 10])).collect();
+        let data = UnseqData::new_with_data(
+            XorName([1; 32]),
+            10000,
+            entries.clone(),
+            BTreeMap::new(),
+            owner,
+        );
+
+        // Page through all the entries, two at a time.
+        let mut cursor = Cursor {
+            start: None,
+            limit: 2,
+        };
+        let mut pages = Vec::new();
+        loop {
+            let page = unwrap!(data.entries_page(&cursor));
+            pages.push(page.items.clone());
+            match page.next_cursor(2) {
+                Some(next) => cursor = next,
+                None => break,
+            }
+        }
+        assert_eq!(
+            pages.iter().map(BTreeMap::len).collect::<Vec<_>>(),
+            vec![2, 2, 1]
+        );
+        assert_eq!(
+            pages.into_iter().flatten().collect::<BTreeMap<_, _>>(),
+            entries
+        );
+
+        // Start keys needn't be present in the data.
+        let page = unwrap!(data.keys_page(&Cursor {
+            start: Some(vec![2, 0]),
+            limit: 10,
+        }));
+        assert_eq!(page.items, vec![vec![3], vec![4]].into_iter().collect());
+        assert!(page.is_last());
+
+        let page = unwrap!(data.values_page(&Cursor {
+            start: Some(vec![1]),
+            limit: 1,
+        }));
+        assert_eq!(page.items, vec![vec![10]]);
+        assert_eq!(page.next, Some(vec![2]));
+
+        // A zero limit would never get past the start key.
+        assert_eq!(
+            data.entries_page(&Cursor {
+                start: None,
+                limit: 0,
+            }),
+            Err(Error::InvalidOperation)
+        );
+
+        // Limits are clamped to the maximum page size.
+        let entries = (0..=MAX_PAGE_LIMIT)
+            .map(|i| (i.to_be_bytes().to_vec(), Vec::new()))
+            .collect();
+        let data =
+            UnseqData::new_with_data(XorName([1; 32]), 10000, entries, BTreeMap::new(), owner);
+        let page = unwrap!(data.keys_page(&Cursor {
+            start: None,
+            limit: u64::MAX,
+        }));
+        assert_eq!(page.items.len() as u64, MAX_PAGE_LIMIT);
+        assert_eq!(page.next, Some(MAX_PAGE_LIMIT.to_be_bytes().to_vec()));
+    }
+
+    #[test]
+    fn pages_of_data() {
+        let owner: PublicKey = SecretKey::random().public_key().into();
+        let value = SeqValue {
+            data: b"value".to_vec(),
+            version: 0,
+        };
+        let entries: BTreeMap<_, _> = vec![(b"key".to_vec(), value.clone())].into_iter().collect();
+        let data: Data = SeqData::new_with_data(
+            XorName([1; 32]),
+            10000,
+            entries.clone(),
+            BTreeMap::new(),
+            owner,
+        )
+        .into();
+        let cursor = Cursor {
+            start: None,
+            limit: 10,
+        };
+
+        assert_eq!(
+            unwrap!(data.entries_page(&cursor)).items,
+            Entries::Seq(entries)
+        );
+        assert_eq!(
+            unwrap!(data.values_page(&cursor)).items,
+            Values::Seq(vec![value])
+        );
+        assert!(unwrap!(data.keys_page(&cursor)).is_last());
+    }
+
+    #[test]
+    fn is_newer_than() {
+        let owner = SecretKey::random().public_key().into();
+        let mut data = SeqData::new(XorName([1; 32]), 10000, owner);
+        unwrap!(data.set_user_permissions(owner, PermissionSet::new(), 1));
+        let data = Data::from(data);
+
+        assert!(data.is_newer_than(0));
+        assert!(!data.is_newer_than(1));
+        assert!(!data.is_newer_than(2));
+
+        // Mutating an entry leaves the fields version as it was, but not the content version.
+        let mut data = match data {
+            Data::Seq(data) => data,
+            Data::Unseq(_) => unreachable!(),
+        };
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec(), 0),
+            owner
+        ));
+        assert_eq!(data.version(), 1);
+        assert_eq!(data.content_version(), 2);
+        assert!(Data::from(data.clone()).is_newer_than(1));
+        assert!(!Data::from(data.clone()).is_newer_than(2));
+
+        // Taking the entries changes the content too.
+        let _ = data.take_entries();
+        assert_eq!(data.content_version(), 3);
+
+        // Diverged replicas, one whose fields changed and one whose entries changed.
+        let mut fields = SeqData::new(XorName([1; 32]), 10000, owner);
+        unwrap!(fields.set_user_permissions(owner, PermissionSet::new(), 1));
+        unwrap!(fields.del_user_permissions(owner, 2));
+        let mut entries = SeqData::new(XorName([1; 32]), 10000, owner);
+        unwrap!(entries.mutate_entries(
+            SeqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec(), 0),
+            owner
+        ));
+        unwrap!(entries.mutate_entries(
+            SeqEntryActions::new().update(b"key".to_vec(), b"other".to_vec(), 1),
+            owner
+        ));
+        assert_eq!(fields.content_version(), 2);
+        assert_eq!(entries.content_version(), 2);
+
+        // Merging takes the greater content version, whichever way round.
+        let mut merged = fields.clone();
+        let _ = unwrap!(merged.merge(&entries));
+        let _ = unwrap!(entries.merge(&fields));
+        assert_eq!(merged.content_version(), 2);
+        assert_eq!(entries.content_version(), 2);
+        assert_eq!(merged, entries);
+    }
+
+    #[test]
+    fn threshold_owners() {
+        let secret_keys: Vec<_> = (0..2).map(|_| SecretKey::random()).collect();
+        let keys: Vec<PublicKey> = secret_keys
+            .iter()
+            .map(|secret_key| secret_key.public_key().into())
+            .collect();
+        let mut data = UnseqData::new(XorName([1; 32]), 10000, keys[0]);
+        unwrap!(data.check_is_owner(keys[0]));
+
+        assert_eq!(data.owners().single(), Some(&keys[0]));
+
+        let owners = unwrap!(Owners::threshold(keys.iter().cloned().collect(), 2));
+        unwrap!(data.change_owner(owners.clone(), 1));
+        assert_eq!(data.owners(), &owners);
+        assert_eq!(data.owners().single(), None);
+
+        // Neither owner alone is enough, so they can't mutate entries as owners.
+        assert_eq!(data.check_is_owner(keys[0]), Err(Error::AccessDenied));
+        assert_eq!(
+            data.check_permissions(Action::Insert, keys[1]),
+            Err(Error::AccessDenied)
+        );
+        let actions = UnseqEntryActions::new().ins(b"key".to_vec(), b"value".to_vec());
+        assert_eq!(
+            data.mutate_entries(actions.clone(), keys[0]),
+            Err(Error::AccessDenied)
+        );
+
+        // A user granted permission can.
+        unwrap!(data.set_user_permissions(keys[1], PermissionSet::new().allow(Action::Insert), 2));
+        unwrap!(data.mutate_entries(actions, keys[1]));
+
+        let message = b"change owner";
+        let signatures = secret_keys
+            .iter()
+            .zip(&keys)
+            .map(|(secret_key, key)| (*key, Signature::Bls(secret_key.sign(message))))
+            .collect();
+        unwrap!(Data::from(data).check_owners_approval(message, &signatures));
+    }
+
+    #[test]
+    fn history() {
+        let owner: PublicKey = SecretKey::random().public_key().into();
+        let key = b"key".to_vec();
+        let value = |data: &[u8], version| SeqValue {
+            data: data.to_vec(),
+            version,
+        };
+
+        let mut data = unwrap!(
+            SeqData::new(XorName([1; 32]), 10000, owner).with_history(HistoryPolicy::KeepAll)
+        );
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().ins(key.clone(), b"a".to_vec(), 0),
+            owner
+        ));
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().update(key.clone(), b"b".to_vec(), 1),
+            owner
+        ));
+        unwrap!(data.mutate_entries(SeqEntryActions::new().del(key.clone(), 2), owner));
+
+        assert_eq!(
+            unwrap!(data.history(&key)),
+            vec![
+                Revision::Value(value(b"a", 0)),
+                Revision::Value(value(b"b", 1)),
+                Revision::Deleted(2),
+            ]
+        );
+        assert_eq!(data.get_at(&key, 0), Ok(Some(&value(b"a", 0))));
+        assert_eq!(data.get_at(&key, 1), Ok(Some(&value(b"b", 1))));
+        assert_eq!(data.get_at(&key, 2), Ok(None));
+        assert_eq!(data.get_at(b"other", 2), Ok(None));
+        assert!(data.shell().history(&key).unwrap().is_empty());
+
+        // Only the latest superseded revisions are kept, alongside the current value.
+        let mut data =
+            unwrap!(SeqData::new(XorName([1; 32]), 10000, owner)
+                .with_history(HistoryPolicy::KeepLast(1)));
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().ins(key.clone(), b"a".to_vec(), 0),
+            owner
+        ));
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().update(key.clone(), b"b".to_vec(), 1),
+            owner
+        ));
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().update(key.clone(), b"c".to_vec(), 2),
+            owner
+        ));
+        assert_eq!(
+            unwrap!(data.history(&key)),
+            vec![
+                Revision::Value(value(b"b", 1)),
+                Revision::Value(value(b"c", 2))
+            ]
+        );
+        assert_eq!(data.get_at(&key, 0), Err(Error::HistoryNotKept));
+        assert_eq!(data.get_at(&key, 1), Ok(Some(&value(b"b", 1))));
+
+        // Without history, only the current values and tombstones are known.
+        let mut data = SeqData::new(XorName([1; 32]), 10000, owner);
+        assert_eq!(data.history(&key), Err(Error::InvalidOperation));
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().ins(key.clone(), b"a".to_vec(), 0),
+            owner
+        ));
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().update(key.clone(), b"b".to_vec(), 1),
+            owner
+        ));
+        assert_eq!(data.get_at(&key, 1), Ok(Some(&value(b"b", 1))));
+        assert_eq!(data.get_at(&key, 0), Err(Error::HistoryNotKept));
+        assert_eq!(data.get_at(b"other", 0), Ok(None));
+        let gone = b"gone".to_vec();
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().ins(gone.clone(), b"a".to_vec(), 0),
+            owner
+        ));
+        unwrap!(data.mutate_entries(SeqEntryActions::new().del(gone.clone(), 1), owner));
+        assert_eq!(data.get_at(&gone, 1), Ok(None));
+        assert_eq!(data.get_at(&gone, 0), Err(Error::HistoryNotKept));
+
+        // Revisions from before history was enabled aren't known either.
+        let data = unwrap!(data.with_history(HistoryPolicy::KeepAll));
+        assert_eq!(data.get_at(&key, 0), Err(Error::HistoryNotKept));
+
+        // Nor are revisions this replica skipped over when merging.
+        let mut replica = unwrap!(
+            SeqData::new(XorName([1; 32]), 10000, owner).with_history(HistoryPolicy::KeepAll)
+        );
+        let _ = unwrap!(replica.merge(&data));
+        assert_eq!(replica.get_at(&key, 1), Ok(Some(&value(b"b", 1))));
+        assert_eq!(replica.get_at(&key, 0), Err(Error::HistoryNotKept));
+
+        assert_eq!(
+            SeqData::new(XorName([1; 32]), 10000, owner).with_history(HistoryPolicy::KeepLast(0)),
+            Err(Error::InvalidOperation)
+        );
+    }
+
+    #[test]
+    fn tombstones() {
+        let owner: PublicKey = SecretKey::random().public_key().into();
+        let key = b"key".to_vec();
+        let mut data = SeqData::new(XorName([1; 32]), 10000, owner);
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new()
+                .ins(key.clone(), b"a".to_vec(), 0)
+                .ins(b"other".to_vec(), b"b".to_vec(), 0),
+            owner
+        ));
+        unwrap!(data.mutate_entries(SeqEntryActions::new().del(key.clone(), 1), owner));
+
+        assert!(!data.keys().contains(&key));
+        assert_eq!(data.entries().len(), 1);
+        assert_eq!(data.values().len(), 1);
+        assert!(data.keys_with_tombstones().contains(&key));
+        assert_eq!(
+            data.entries_with_tombstones().get(&key),
+            Some(&Revision::Deleted(1))
+        );
+        assert_eq!(data.values_with_tombstones().len(), 2);
+
+        // A stale replica can't resurrect the entry from version 0.
+        let mut errors = BTreeMap::new();
+        let _ = errors.insert(key.clone(), EntryError::Deleted(1));
+        assert_eq!(
+            data.mutate_entries(
+                SeqEntryActions::new().ins(key.clone(), b"a".to_vec(), 0),
+                owner
+            ),
+            Err(Error::InvalidEntryActions(errors))
+        );
+
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().ins(key.clone(), b"c".to_vec(), 2),
+            owner
+        ));
+        assert!(data.tombstones().is_empty());
+        assert_eq!(unwrap!(data.get(&key)).version, 2);
+
+        // There's no successor to a deletion at the maximum version.
+        unwrap!(data.mutate_entries(
+            SeqEntryActions::new().ins(b"max".to_vec(), b"a".to_vec(), u64::MAX - 1),
+            owner
+        ));
+        unwrap!(data.mutate_entries(SeqEntryActions::new().del(b"max".to_vec(), u64::MAX), owner));
+        let mut errors = BTreeMap::new();
+        let _ = errors.insert(b"max".to_vec(), EntryError::Deleted(u64::MAX));
+        assert_eq!(
+            data.mutate_entries(
+                SeqEntryActions::new().ins(b"max".to_vec(), b"b".to_vec(), 0),
+                owner
+            ),
+            Err(Error::InvalidEntryActions(errors))
+        );
+    }
+
+    #[test]
+    fn merge_unseq() {
+        let owner: PublicKey = SecretKey::random().public_key().into();
+        let user: PublicKey = SecretKey::random().public_key().into();
+        let mut ours = UnseqData::new(XorName([1; 32]), 10000, owner);
+        let mut theirs = ours.clone();
+
+        unwrap!(ours.mutate_entries(
+            UnseqEntryActions::new()
+                .ins(b"a".to_vec(), b"1".to_vec())
+                .ins(b"b".to_vec(), b"1".to_vec()),
+            owner
+        ));
+        unwrap!(theirs.mutate_entries(
+            UnseqEntryActions::new()
+                .ins(b"b".to_vec(), b"2".to_vec())
+                .ins(b"c".to_vec(), b"1".to_vec()),
+            owner
+        ));
+        unwrap!(theirs.set_user_permissions(user, PermissionSet::new().allow(Action::Read), 1));
+
+        let mut merged = ours.clone();
+        let report = unwrap!(merged.merge(&theirs));
+        let mut expected = MergeReport::default();
+        let _ = expected.entries.insert(b"b".to_vec());
+        let _ = expected.entries.insert(b"c".to_vec());
+        let _ = expected.permissions.insert(user);
+        assert_eq!(report, expected);
+        assert_eq!(merged.get(b"b"), Some(&b"2".to_vec()));
+        assert_eq!(merged.keys().len(), 3);
+        assert_eq!(merged.version(), 1);
+
+        // Merging the other way round gives the same data.
+        let _ = unwrap!(theirs.merge(&ours));
+        assert_eq!(theirs, merged);
+        assert!(unwrap!(merged.merge(&theirs)).is_empty());
+
+        let other = UnseqData::new(XorName([2; 32]), 10000, owner);
+        assert_eq!(merged.merge(&other), Err(Error::InvalidOperation));
+    }
+
+    #[test]
+    fn merge_seq() {
+        let owner: PublicKey = SecretKey::random().public_key().into();
+        let new_owner: PublicKey = SecretKey::random().public_key().into();
+        let mut ours = SeqData::new(XorName([1; 32]), 10000, owner);
+        unwrap!(ours.mutate_entries(
+            SeqEntryActions::new()
+                .ins(b"a".to_vec(), b"1".to_vec(), 0)
+                .ins(b"b".to_vec(), b"1".to_vec(), 0),
+            owner
+        ));
+        let mut theirs = ours.clone();
+
+        unwrap!(ours.mutate_entries(
+            SeqEntryActions::new().update(b"a".to_vec(), b"2".to_vec(), 1),
+            owner
+        ));
+        unwrap!(ours.mutate_entries(
+            SeqEntryActions::new().update(b"b".to_vec(), b"2".to_vec(), 1),
+            owner
+        ));
+        unwrap!(theirs.mutate_entries(SeqEntryActions::new().del(b"b".to_vec(), 1), owner));
+        unwrap!(theirs.change_owner(new_owner, 1));
+
+        let mut merged = ours.clone();
+        let report = unwrap!(merged.merge(&theirs));
+        let mut expected = MergeReport::default();
+        let _ = expected.entries.insert(b"b".to_vec());
+        expected.owners = true;
+        assert_eq!(report, expected);
+        assert_eq!(unwrap!(merged.get(b"a")).data, b"2".to_vec());
+        assert_eq!(merged.get(b"b"), None);
+        assert_eq!(merged.tombstones().get(&b"b".to_vec()), Some(&1));
+        assert_eq!(merged.owners(), &Owners::from(new_owner));
+
+        let _ = unwrap!(theirs.merge(&ours));
+        assert_eq!(theirs, merged);
+    }
+
+    #[test]
+    fn diff() {
+        let owner: PublicKey = SecretKey::random().public_key().into();
+        let user: PublicKey = SecretKey::random().public_key().into();
+        let mut old = SeqData::new(XorName([1; 32]), 10000, owner);
+        unwrap!(old.set_user_permissions(user, PermissionSet::new().allow(Action::Read), 1));
+        unwrap!(old.mutate_entries(
+            SeqEntryActions::new()
+                .ins(b"a".to_vec(), b"1".to_vec(), 0)
+                .ins(b"b".to_vec(), b"1".to_vec(), 0)
+                .ins(b"c".to_vec(), b"1".to_vec(), 0),
+            owner
+        ));
+        let old = Data::from(old);
+
+        let mut new = old.clone();
+        unwrap!(new.mutate_entries(
+            SeqEntryActions::new()
+                .update(b"a".to_vec(), b"2".to_vec(), 1)
+                .del(b"b".to_vec(), 1)
+                .ins(b"d".to_vec(), b"1".to_vec(), 0)
+                .into(),
+            owner
+        ));
+        unwrap!(new.del_user_permissions(user, 2));
+
+        let diff = unwrap!(Data::diff(&old, &new));
+        let mut added = BTreeMap::new();
+        let _ = added.insert(
+            b"d".to_vec(),
+            SeqValue {
+                data: b"1".to_vec(),
+                version: 0,
+            },
+        );
+        assert_eq!(diff.added, Entries::Seq(added));
+        assert_eq!(diff.permissions.get(&user), Some(&None));
+        assert_eq!(diff.owners, None);
+        assert_eq!((diff.old_version, diff.new_version), (1, 2));
+        assert_eq!(diff.tombstones.get(b"b".as_ref()), Some(&1));
+        assert!(unwrap!(Data::diff(&new, &new)).is_empty());
+
+        // Applying the entry actions to a copy of the old snapshot gives the new entries.
+        let mut copy = old.clone();
+        for actions in diff.entry_actions() {
+            unwrap!(copy.mutate_entries(actions, owner));
+        }
+        let cursor = Cursor {
+            start: None,
+            limit: 10,
+        };
+        assert_eq!(copy.entries_page(&cursor), new.entries_page(&cursor));
+
+        let unseq = Data::from(UnseqData::new(XorName([1; 32]), 10000, owner));
+        assert_eq!(Data::diff(&old, &unseq), Err(Error::InvalidOperation));
+    }
+
+    #[test]
+    fn diff_reinserted_entry() {
+        let owner: PublicKey = SecretKey::random().public_key().into();
+        let mut old = unwrap!(
+            SeqData::new(XorName([1; 32]), 10000, owner).with_history(HistoryPolicy::KeepAll)
+        );
+        unwrap!(old.mutate_entries(
+            SeqEntryActions::new().ins(b"a".to_vec(), b"1".to_vec(), 0),
+            owner
+        ));
+        let mut new = old.clone();
+        unwrap!(new.mutate_entries(SeqEntryActions::new().del(b"a".to_vec(), 1), owner));
+        unwrap!(new.mutate_entries(
+            SeqEntryActions::new().ins(b"a".to_vec(), b"2".to_vec(), 2),
+            owner
+        ));
+        let old = Data::from(old);
+        let new = Data::from(new);
+
+        // The entry is reported as removed and added again, not as updated.
+        let diff = unwrap!(Data::diff(&old, &new));
+        assert!(diff.updated.is_empty());
+        let value = |data: &[u8], version| {
+            let mut entries = BTreeMap::new();
+            let _ = entries.insert(
+                b"a".to_vec(),
+                SeqValue {
+                    data: data.to_vec(),
+                    version,
+                },
+            );
+            Entries::Seq(entries)
+        };
+        assert_eq!(diff.removed, value(b"1", 0));
+        assert_eq!(diff.added, value(b"2", 2));
+        assert_eq!(diff.tombstones.get(b"a".as_ref()), Some(&1));
+
+        let actions = diff.entry_actions();
+        assert_eq!(actions.len(), 2);
+        let mut copy = old.clone();
+        for actions in actions {
+            unwrap!(copy.mutate_entries(actions, owner));
+        }
+        let cursor = Cursor {
+            start: None,
+            limit: 10,
+        };
+        assert_eq!(copy.entries_page(&cursor), new.entries_page(&cursor));
+    }
+
+    #[test]
+    fn conditional_unseq_actions() {
+        let owner: PublicKey = SecretKey::random().public_key().into();
+        let user: PublicKey = SecretKey::random().public_key().into();
+        let mut data = UnseqData::new(XorName([1; 32]), 10000, owner);
+        unwrap!(data.mutate_entries(
+            UnseqEntryActions::new()
+                .ins(b"a".to_vec(), b"1".to_vec())
+                .ins(b"b".to_vec(), b"1".to_vec()),
+            owner
+        ));
+
+        // A single failed condition aborts all the actions.
+        let mut errors = BTreeMap::new();
+        let _ = errors.insert(b"b".to_vec(), EntryError::ValueMismatch);
+        let _ = errors.insert(b"c".to_vec(), EntryError::NoSuchEntry);
+        assert_eq!(
+            data.mutate_entries(
+                UnseqEntryActions::new()
+                    .update_if_equals(b"a".to_vec(), b"1".to_vec(), b"2".to_vec())
+                    .del_if_equals(b"b".to_vec(), b"2".to_vec())
+                    .del_if_equals(b"c".to_vec(), b"1".to_vec()),
+                owner
+            ),
+            Err(Error::InvalidEntryActions(errors))
+        );
+        assert_eq!(data.get(b"a"), Some(&b"1".to_vec()));
+
+        unwrap!(data.mutate_entries(
+            UnseqEntryActions::new()
+                .update_if_equals(b"a".to_vec(), b"1".to_vec(), b"2".to_vec())
+                .del_if_equals(b"b".to_vec(), b"1".to_vec())
+                .upsert(b"c".to_vec(), b"1".to_vec()),
+            owner
+        ));
+        assert_eq!(data.get(b"a"), Some(&b"2".to_vec()));
+        assert_eq!(data.get(b"b"), None);
+        assert_eq!(data.get(b"c"), Some(&b"1".to_vec()));
+
+        // Upserting an existing entry needs the permission to update it.
+        unwrap!(data.set_user_permissions(user, PermissionSet::new().allow(Action::Insert), 1));
+        assert_eq!(
+            data.mutate_entries(
+                UnseqEntryActions::new().upsert(b"c".to_vec(), b"2".to_vec()),
+                user
+            ),
+            Err(Error::AccessDenied)
+        );
+        unwrap!(data.mutate_entries(
+            UnseqEntryActions::new().upsert(b"d".to_vec(), b"1".to_vec()),
+            user
+        ));
+    }
 }
@@ -0,0 +1,313 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Error, PublicKey, Result, Signature};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+};
+use threshold_crypto::{PublicKeySet, SignatureShare};
+
+/// Who owns a piece of data, and so whose approval is needed to delete it or change its owners or
+/// permissions.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum Owners {
+    /// A single owner.
+    Single(PublicKey),
+    /// A threshold of a set of keys must approve. Unless the threshold is one, a request signed by
+    /// a single owner doesn't carry enough approval, so its approval has to be checked with
+    /// `check_approval`.
+    Threshold(ThresholdOwners),
+    /// A group holding shares of a BLS key. More than the set's threshold of shares must approve,
+    /// either individually or as a combined signature verifying against the group's key.
+    BlsGroup(PublicKeySet),
+}
+
+impl Owners {
+    /// Constructs `Owners` needing `threshold` of `keys` to approve.
+    ///
+    /// Returns `Err(Error::InvalidOwners)` if `threshold` is zero or exceeds the number of keys.
+    pub fn threshold(keys: BTreeSet<PublicKey>, threshold: usize) -> Result<Self> {
+        Ok(Owners::Threshold(ThresholdOwners::new(keys, threshold)?))
+    }
+
+    /// Returns the key of the single owner, or `None` if there are several owners.
+    pub fn single(&self) -> Option<&PublicKey> {
+        match self {
+            Owners::Single(key) => Some(key),
+            Owners::Threshold(_) | Owners::BlsGroup(_) => None,
+        }
+    }
+
+    /// Checks whether the approval of `requester` alone is enough, i.e. whether it's the single
+    /// owner, one of a threshold of one, or the key of a BLS group.
+    ///
+    /// Returns `Err(Error::AccessDenied)` otherwise.
+    pub fn check_is_owner(&self, requester: PublicKey) -> Result<()> {
+        let is_owner = match self {
+            Owners::Single(key) => *key == requester,
+            Owners::Threshold(owners) => owners.threshold == 1 && owners.keys.contains(&requester),
+            Owners::BlsGroup(key_set) => PublicKey::Bls(key_set.public_key()) == requester,
+        };
+        if is_owner {
+            Ok(())
+        } else {
+            Err(Error::AccessDenied)
+        }
+    }
+
+    /// Checks that `signatures` over `data` carry enough owners' approval. Signatures from keys
+    /// other than the owners' are ignored.
+    ///
+    /// Returns `Err(Error::InvalidSignature)` if an owner's signature is invalid, or
+    /// `Err(Error::AccessDenied)` if too few owners have signed.
+    pub fn check_approval(
+        &self,
+        data: &[u8],
+        signatures: &BTreeMap<PublicKey, Signature>,
+    ) -> Result<()> {
+        let (owner_keys, threshold) = match self {
+            Owners::Single(key) => (vec![*key], 1),
+            Owners::Threshold(owners) => (owners.keys.iter().cloned().collect(), owners.threshold),
+            Owners::BlsGroup(key_set) => (vec![PublicKey::Bls(key_set.public_key())], 1),
+        };
+
+        let mut approvals = 0;
+        for key in owner_keys {
+            if let Some(signature) = signatures.get(&key) {
+                key.verify(signature, data)?;
+                approvals += 1;
+            }
+        }
+        if threshold > 0 && approvals >= threshold {
+            Ok(())
+        } else {
+            Err(Error::AccessDenied)
+        }
+    }
+
+    /// Checks that BLS signature `shares` over `data`, keyed on the index of the signer in the
+    /// group, carry enough approval, by verifying each share and then their combined signature.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` unless these are the owners of a BLS group,
+    /// `Err(Error::InvalidSignature)` if any share is invalid, or `Err(Error::AccessDenied)` if
+    /// there aren't enough shares.
+    pub fn check_bls_shares(
+        &self,
+        data: &[u8],
+        shares: &BTreeMap<usize, SignatureShare>,
+    ) -> Result<()> {
+        let key_set = match self {
+            Owners::BlsGroup(key_set) => key_set,
+            Owners::Single(_) | Owners::Threshold(_) => return Err(Error::InvalidOperation),
+        };
+
+        for (index, share) in shares {
+            if !key_set.public_key_share(*index).verify(share, data) {
+                return Err(Error::InvalidSignature);
+            }
+        }
+        if shares.len() <= key_set.threshold() {
+            return Err(Error::AccessDenied);
+        }
+
+        let signature = key_set
+            .combine_signatures(shares)
+            .map_err(|_| Error::InvalidSignature)?;
+        PublicKey::Bls(key_set.public_key()).verify(&Signature::Bls(signature), data)
+    }
+}
+
+/// Owners' approval of a request, sent alongside it in `Message::Request` and checked against the
+/// owners of the data it targets by `Message::verify`.
+#[derive(Clone, Eq, PartialEq, Hash, Serialize, Deserialize, Debug)]
+pub enum Approvals {
+    /// Signatures by owner keys, as checked by `Owners::check_approval`.
+    Signatures(BTreeMap<PublicKey, Signature>),
+    /// BLS signature shares keyed on the index of the signer in the group, as checked by
+    /// `Owners::check_bls_shares`.
+    BlsShares(BTreeMap<usize, SignatureShare>),
+}
+
+impl Approvals {
+    /// Checks that these approvals of `data` are enough for `owners`.
+    pub fn check(&self, owners: &Owners, data: &[u8]) -> Result<()> {
+        match self {
+            Approvals::Signatures(signatures) => owners.check_approval(data, signatures),
+            Approvals::BlsShares(shares) => owners.check_bls_shares(data, shares),
+        }
+    }
+}
+
+/// Keys of which a threshold must approve. The threshold is always at least one and at most the
+/// number of keys, including when deserialised.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+#[serde(try_from = "UncheckedThresholdOwners")]
+pub struct ThresholdOwners {
+    keys: BTreeSet<PublicKey>,
+    threshold: usize,
+}
+
+impl ThresholdOwners {
+    /// Constructs `ThresholdOwners` needing `threshold` of `keys` to approve.
+    ///
+    /// Returns `Err(Error::InvalidOwners)` if `threshold` is zero or exceeds the number of keys.
+    pub fn new(keys: BTreeSet<PublicKey>, threshold: usize) -> Result<Self> {
+        if threshold == 0 || threshold > keys.len() {
+            return Err(Error::InvalidOwners);
+        }
+        Ok(Self { keys, threshold })
+    }
+
+    /// Returns the keys of the owners.
+    pub fn keys(&self) -> &BTreeSet<PublicKey> {
+        &self.keys
+    }
+
+    /// Returns the number of owners who must approve.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+}
+
+// Serialised form of `ThresholdOwners`, validated before use.
+#[derive(Deserialize)]
+struct UncheckedThresholdOwners {
+    keys: BTreeSet<PublicKey>,
+    threshold: usize,
+}
+
+impl TryFrom<UncheckedThresholdOwners> for ThresholdOwners {
+    type Error = Error;
+
+    fn try_from(owners: UncheckedThresholdOwners) -> Result<Self> {
+        Self::new(owners.keys, owners.threshold)
+    }
+}
+
+impl From<PublicKey> for Owners {
+    fn from(key: PublicKey) -> Self {
+        Owners::Single(key)
+    }
+}
+
+impl From<PublicKeySet> for Owners {
+    fn from(key_set: PublicKeySet) -> Self {
+        Owners::BlsGroup(key_set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils;
+    use threshold_crypto::{SecretKey, SecretKeySet};
+    use unwrap::{unwrap, unwrap_err};
+
+    #[test]
+    fn threshold() {
+        let secret_keys: Vec<_> = (0..3).map(|_| SecretKey::random()).collect();
+        let keys: BTreeSet<_> = secret_keys
+            .iter()
+            .map(|secret_key| PublicKey::Bls(secret_key.public_key()))
+            .collect();
+        assert_eq!(
+            unwrap_err!(Owners::threshold(keys.clone(), 0)),
+            Error::InvalidOwners
+        );
+        assert_eq!(
+            unwrap_err!(Owners::threshold(keys.clone(), 4)),
+            Error::InvalidOwners
+        );
+
+        let owners = unwrap!(Owners::threshold(keys.clone(), 2));
+        let owner = PublicKey::Bls(secret_keys[0].public_key());
+        assert_eq!(owners.check_is_owner(owner), Err(Error::AccessDenied));
+
+        let data = b"data";
+        let mut signatures = BTreeMap::new();
+        let _ = signatures.insert(owner, Signature::Bls(secret_keys[0].sign(data)));
+        let stranger = SecretKey::random();
+        let _ = signatures.insert(
+            PublicKey::Bls(stranger.public_key()),
+            Signature::Bls(stranger.sign(data)),
+        );
+        assert_eq!(
+            owners.check_approval(data, &signatures),
+            Err(Error::AccessDenied)
+        );
+
+        let _ = signatures.insert(
+            PublicKey::Bls(secret_keys[1].public_key()),
+            Signature::Bls(secret_keys[1].sign(data)),
+        );
+        unwrap!(owners.check_approval(data, &signatures));
+
+        let _ = signatures.insert(
+            PublicKey::Bls(secret_keys[2].public_key()),
+            Signature::Bls(secret_keys[0].sign(data)),
+        );
+        assert_eq!(
+            owners.check_approval(data, &signatures),
+            Err(Error::InvalidSignature)
+        );
+
+        // An invalid threshold can't be deserialised either.
+        let serialised = utils::serialise(&(1u32, &keys, 0usize));
+        assert!(bincode::deserialize::<Owners>(&serialised).is_err());
+        let serialised = utils::serialise(&(1u32, &keys, 4usize));
+        assert!(bincode::deserialize::<Owners>(&serialised).is_err());
+        let serialised = utils::serialise(&(1u32, &keys, 2usize));
+        assert_eq!(unwrap!(bincode::deserialize::<Owners>(&serialised)), owners);
+    }
+
+    #[test]
+    fn bls_group() {
+        let mut rng = rand::thread_rng();
+        let secret_key_set = SecretKeySet::random(1, &mut rng);
+        let owners = Owners::from(secret_key_set.public_keys());
+        let group_key = PublicKey::Bls(secret_key_set.public_keys().public_key());
+        unwrap!(owners.check_is_owner(group_key));
+
+        let data = b"data";
+        let shares: BTreeMap<_, _> = (0..3)
+            .map(|index| (index, secret_key_set.secret_key_share(index).sign(data)))
+            .collect();
+
+        let mut too_few = shares.clone();
+        let _ = too_few.remove(&2);
+        let _ = too_few.remove(&1);
+        assert_eq!(
+            owners.check_bls_shares(data, &too_few),
+            Err(Error::AccessDenied)
+        );
+        unwrap!(owners.check_bls_shares(data, &shares));
+
+        let mut wrong_index = shares.clone();
+        let share = unwrap!(wrong_index.remove(&2));
+        let _ = wrong_index.insert(5, share);
+        assert_eq!(
+            owners.check_bls_shares(data, &wrong_index),
+            Err(Error::InvalidSignature)
+        );
+
+        // The combined signature approves too.
+        let combined = unwrap!(secret_key_set.public_keys().combine_signatures(&shares));
+        let mut signatures = BTreeMap::new();
+        let _ = signatures.insert(group_key, Signature::Bls(combined));
+        unwrap!(owners.check_approval(data, &signatures));
+
+        let single = Owners::from(group_key);
+        assert_eq!(
+            single.check_bls_shares(data, &shares),
+            Err(Error::InvalidOperation)
+        );
+    }
+}
@@ -0,0 +1,232 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{Clock, Error, MessageId, Request, Response, Result, SystemClock};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+/// Requests which have been sent and are awaiting a response, keyed on their `MessageId`.
+pub struct PendingRequests<C: Clock = SystemClock> {
+    clock: C,
+    timeout: Duration,
+    // Requests along with the time they were sent.
+    requests: HashMap<MessageId, (Request, Instant)>,
+}
+
+impl PendingRequests<SystemClock> {
+    /// Constructs a new tracker in which requests time out after `timeout`.
+    pub fn new(timeout: Duration) -> Self {
+        Self::with_clock(timeout, SystemClock)
+    }
+}
+
+impl<C: Clock> PendingRequests<C> {
+    /// Constructs a new tracker which uses `clock` as its source of time.
+    pub fn with_clock(timeout: Duration, clock: C) -> Self {
+        Self {
+            clock,
+            timeout,
+            requests: HashMap::new(),
+        }
+    }
+
+    /// Records `request` as sent with the given `message_id`.
+    ///
+    /// Returns `Err(Error::DuplicateMessageId)` if a request with that ID is already pending.
+    pub fn insert(&mut self, message_id: MessageId, request: Request) -> Result<()> {
+        if self.requests.contains_key(&message_id) {
+            return Err(Error::DuplicateMessageId);
+        }
+        let _ = self
+            .requests
+            .insert(message_id, (request, self.clock.now()));
+        Ok(())
+    }
+
+    /// Matches `response` against the request pending under `message_id`, and if it is the
+    /// expected type of response, removes and returns that request.
+    ///
+    /// Returns:
+    /// * `Err(Error::NoSuchPendingRequest)` if no request is pending under `message_id`.
+    /// * `Err(Error::UnexpectedResponse)` if `response` is the wrong type for the request. The
+    ///   request stays pending, as the proper response may still arrive.
+    /// * `Err(Error::RequestTimedOut)` if the request has timed out. The request is removed.
+    pub fn handle_response(
+        &mut self,
+        message_id: MessageId,
+        response: &Response,
+    ) -> Result<Request> {
+        let sent_at = match self.requests.get(&message_id) {
+            Some((request, sent_at)) => {
+                if !request.expects(response) {
+                    return Err(Error::UnexpectedResponse);
+                }
+                *sent_at
+            }
+            None => return Err(Error::NoSuchPendingRequest),
+        };
+
+        let (request, _) = self
+            .requests
+            .remove(&message_id)
+            .ok_or(Error::NoSuchPendingRequest)?;
+        if self.is_expired(sent_at, self.clock.now()) {
+            return Err(Error::RequestTimedOut);
+        }
+        Ok(request)
+    }
+
+    /// Removes all requests which have timed out, returning each with the `Response` to treat it
+    /// as having received, i.e. one holding `Error::RequestTimedOut`.
+    ///
+    /// The requests are returned in the order they were sent.
+    pub fn remove_expired(&mut self) -> Vec<(MessageId, Request, Response)> {
+        let now = self.clock.now();
+        let mut expired: Vec<_> = self
+            .requests
+            .iter()
+            .filter(|(_, (_, sent_at))| self.is_expired(*sent_at, now))
+            .map(|(message_id, (_, sent_at))| (*sent_at, *message_id))
+            .collect();
+        expired.sort_by_key(|(sent_at, _)| *sent_at);
+
+        expired
+            .into_iter()
+            .filter_map(|(_, message_id)| {
+                let (request, _) = self.requests.remove(&message_id)?;
+                let response = request.error_response(Error::RequestTimedOut);
+                Some((message_id, request, response))
+            })
+            .collect()
+    }
+
+    /// Returns the request pending under `message_id`, if any.
+    pub fn get(&self, message_id: &MessageId) -> Option<&Request> {
+        self.requests.get(message_id).map(|(request, _)| request)
+    }
+
+    /// Returns true if a request is pending under `message_id`.
+    pub fn contains(&self, message_id: &MessageId) -> bool {
+        self.requests.contains_key(message_id)
+    }
+
+    /// Returns the number of pending requests, including any which have timed out but haven't yet
+    /// been removed.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Returns true if there are no pending requests.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    fn is_expired(&self, sent_at: Instant, now: Instant) -> bool {
+        now.duration_since(sent_at) >= self.timeout
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{clock::MockClock, IDataAddress, XorName};
+    use unwrap::{unwrap, unwrap_err};
+
+    fn get_idata() -> Request {
+        Request::GetIData(IDataAddress::Pub(XorName([1; 32])))
+    }
+
+    #[test]
+    fn matches_responses() {
+        let mut pending = PendingRequests::new(Duration::from_secs(60));
+        let id = MessageId::new();
+        unwrap!(pending.insert(id, get_idata()));
+        assert!(pending.contains(&id));
+
+        // Wrong type of response is rejected, leaving the request pending.
+        assert_eq!(
+            pending.handle_response(id, &Response::Mutation(Ok(()))),
+            Err(Error::UnexpectedResponse)
+        );
+        assert_eq!(pending.len(), 1);
+
+        // Unknown ID is rejected.
+        let response = Response::GetIData(Err(Error::NoSuchData));
+        assert_eq!(
+            pending.handle_response(MessageId::new(), &response),
+            Err(Error::NoSuchPendingRequest)
+        );
+
+        // Matching response, even an error, completes the request.
+        assert_eq!(unwrap!(pending.handle_response(id, &response)), get_idata());
+        assert!(pending.is_empty());
+        assert_eq!(
+            pending.handle_response(id, &response),
+            Err(Error::NoSuchPendingRequest)
+        );
+    }
+
+    #[test]
+    fn rejects_duplicate_ids() {
+        let mut pending = PendingRequests::new(Duration::from_secs(60));
+        let id = MessageId::new();
+        unwrap!(pending.insert(id, get_idata()));
+        assert_eq!(
+            unwrap_err!(pending.insert(id, Request::GetBalance)),
+            Error::DuplicateMessageId
+        );
+        assert_eq!(pending.get(&id), Some(&get_idata()));
+    }
+
+    #[test]
+    fn times_out() {
+        let clock = MockClock::new();
+        let mut pending = PendingRequests::with_clock(Duration::from_secs(10), clock.clone());
+
+        let id_0 = MessageId::new();
+        let id_1 = MessageId::new();
+        let id_2 = MessageId::new();
+        unwrap!(pending.insert(id_0, get_idata()));
+        clock.advance(Duration::from_secs(1));
+        unwrap!(pending.insert(id_1, Request::GetBalance));
+        clock.advance(Duration::from_secs(5));
+        unwrap!(pending.insert(id_2, get_idata()));
+        assert!(pending.remove_expired().is_empty());
+
+        clock.advance(Duration::from_secs(5));
+        let expired = pending.remove_expired();
+        assert_eq!(
+            expired,
+            vec![
+                (
+                    id_0,
+                    get_idata(),
+                    Response::GetIData(Err(Error::RequestTimedOut))
+                ),
+                (
+                    id_1,
+                    Request::GetBalance,
+                    Response::GetBalance(Err(Error::RequestTimedOut))
+                ),
+            ]
+        );
+        assert_eq!(pending.len(), 1);
+
+        // A late response is rejected even if the request hasn't been removed yet.
+        clock.advance(Duration::from_secs(5));
+        let response = Response::GetIData(Err(Error::NoSuchData));
+        assert_eq!(
+            pending.handle_response(id_2, &response),
+            Err(Error::RequestTimedOut)
+        );
+        assert!(pending.is_empty());
+    }
+}
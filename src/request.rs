@@ -8,18 +8,24 @@
 // Software.
 
 mod login_packet;
+mod signed_request;
 
 pub use self::login_packet::{LoginPacket, MAX_LOGIN_PACKET_BYTES};
+pub use self::signed_request::SignedRequest;
 use crate::{
-    AData, ADataAddress, ADataAppendOperation, ADataIndex, ADataOwner, ADataPubPermissions,
-    ADataUnpubPermissions, ADataUser, AppPermissions, Coins, Error, IData, IDataAddress, MData,
-    MDataAddress, MDataEntryActions, MDataPermissionSet, PublicKey, Response, TransactionId,
-    XorName,
+    response::Kind as ResponseKind, utils, AData, ADataAddress, ADataAppendOperation, ADataCursor,
+    ADataIndex, ADataIndices, ADataOwner, ADataPubPermissions, ADataUnpubPermissions, ADataUser,
+    AppPermissions, Coins, DataAddress, Error, IData, IDataAddress, MData, MDataAddress,
+    MDataCursor, MDataDiff, MDataEntryActions, MDataPermissionSet, MDataSeqEntryAction,
+    MDataUnseqEntryAction, PricingPolicy, PublicKey, Response, TransactionId, XorName,
 };
-use serde::{Deserialize, Serialize};
-use std::fmt;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use std::{cell::Cell, convert::TryFrom, fmt, iter};
 
 /// The type of a `Request`.
+///
+/// Variants are ordered from least to most privileged; the type of a `Request::Batch` is the
+/// greatest type of the requests in it.
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
 pub enum Type {
     /// Request is a Get for public data.
@@ -32,6 +38,46 @@ pub enum Type {
     Transaction,
 }
 
+/// How vaults handle a `Request::Batch` containing mutations or transactions.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum BatchMode {
+    /// Either all the requests in the batch succeed, or none of the mutations and transactions in
+    /// it are applied. In the latter case, the responses to the requests which didn't fail hold
+    /// `Error::BatchAborted`.
+    Atomic,
+    /// Each request in the batch is handled independently, in order, and may fail without
+    /// affecting the others.
+    BestEffort,
+}
+
+/// Data whose changes a client can subscribe to.
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize, Debug)]
+pub enum SubscriptionTarget {
+    /// MutableData at the given address.
+    MData(MDataAddress),
+    /// AppendOnlyData at the given address.
+    AData(ADataAddress),
+}
+
+impl SubscriptionTarget {
+    /// Returns true if the target data is published.
+    pub fn is_pub(&self) -> bool {
+        match *self {
+            SubscriptionTarget::MData(_) => false,
+            SubscriptionTarget::AData(address) => address.is_pub(),
+        }
+    }
+}
+
+impl From<SubscriptionTarget> for DataAddress {
+    fn from(target: SubscriptionTarget) -> Self {
+        match target {
+            SubscriptionTarget::MData(address) => address.into(),
+            SubscriptionTarget::AData(address) => address.into(),
+        }
+    }
+}
+
 /// RPC Request that is sent to vaults.
 #[allow(clippy::large_enum_variant)]
 #[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize)]
@@ -286,6 +332,102 @@ pub enum Request {
         /// Incremented version
         version: u64,
     },
+    //
+    // ===== Paginated listings =====
+    //
+    /// List a page of MutableData entries.
+    ListMDataEntriesPage {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Position of the page.
+        cursor: MDataCursor,
+    },
+    /// List a page of MutableData keys.
+    ListMDataKeysPage {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Position of the page.
+        cursor: MDataCursor,
+    },
+    /// List a page of MutableData values.
+    ListMDataValuesPage {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Position of the page.
+        cursor: MDataCursor,
+    },
+    /// Get a page of entries from an AppendOnlyData object on the network.
+    GetADataRangePage {
+        /// AppendOnlyData address.
+        address: ADataAddress,
+        /// Position of the page.
+        cursor: ADataCursor,
+    },
+    //
+    // ===== Batch =====
+    //
+    /// Several requests handled together, answered by a `Response::Batch` holding the response to
+    /// each request in the same order.
+    ///
+    /// The batch must be signed if any of the requests in it must be. Batches can't be nested,
+    /// which `Request::validate` checks, and a nested batch fails to deserialise.
+    Batch {
+        /// Requests in the batch.
+        #[serde(deserialize_with = "deserialise_batch")]
+        requests: Vec<Request>,
+        /// How the batch is handled if it contains mutations or transactions.
+        mode: BatchMode,
+    },
+    //
+    // ===== Conditional gets =====
+    //
+    /// Get MutableData if its content version is greater than `known_version`. See
+    /// `MData::is_newer_than`.
+    GetMDataIfNewer {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Content version of the copy already held by the requester.
+        known_version: u64,
+    },
+    /// Get AppendOnlyData if any of its indices is greater than in `known_indices`.
+    GetADataIfNewer {
+        /// AppendOnlyData address.
+        address: ADataAddress,
+        /// Indices of the copy already held by the requester.
+        known_indices: ADataIndices,
+    },
+    //
+    // ===== Subscriptions =====
+    //
+    /// Subscribe to `Notification`s of changes to the target data.
+    ///
+    /// Subscribing to unpublished data requires permission to read it. Notifications are sent to
+    /// the requester until it unsubscribes or disconnects.
+    Subscribe(SubscriptionTarget),
+    /// Unsubscribe from `Notification`s of changes to the target data.
+    Unsubscribe(SubscriptionTarget),
+    //
+    // ===== MData history =====
+    //
+    /// Get the revisions of an entry in sequenced MutableData with history enabled.
+    GetMDataEntryHistory {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Key of the entry.
+        key: Vec<u8>,
+    },
+    /// Get the value an entry in sequenced MutableData held at the given entry version.
+    ///
+    /// Fails with `Error::NoSuchEntry` if the entry didn't exist or had been deleted by then, and
+    /// with `Error::HistoryNotKept` if that isn't known. See `SeqMutableData::get_at`.
+    GetMDataValueAt {
+        /// MutableData address.
+        address: MDataAddress,
+        /// Key of the entry.
+        key: Vec<u8>,
+        /// Version of the entry.
+        version: u64,
+    },
 }
 
 impl Request {
@@ -315,7 +457,9 @@ impl Request {
             | GetADataPermissions { address, .. }
             | GetPubADataUserPermissions { address, .. }
             | GetUnpubADataUserPermissions { address, .. }
-            | GetADataOwners { address, .. } => {
+            | GetADataOwners { address, .. }
+            | GetADataRangePage { address, .. }
+            | GetADataIfNewer { address, .. } => {
                 if address.is_pub() {
                     Type::PublicGet
                 } else {
@@ -333,7 +477,23 @@ impl Request {
             | ListMDataKeys(_)
             | ListMDataValues(_)
             | ListMDataPermissions(_)
-                | ListMDataUserPermissions { .. } => Type::PrivateGet,
+            | ListMDataUserPermissions { .. }
+            | ListMDataEntriesPage { .. }
+            | ListMDataKeysPage { .. }
+            | ListMDataValuesPage { .. }
+            | GetMDataIfNewer { .. }
+            | GetMDataEntryHistory { .. }
+            | GetMDataValueAt { .. } => Type::PrivateGet,
+
+            // Subscriptions
+
+            Subscribe(target) | Unsubscribe(target) => {
+                if target.is_pub() {
+                    Type::PublicGet
+                } else {
+                    Type::PrivateGet
+                }
+            }
 
             // Coins
             GetBalance |
@@ -376,9 +536,317 @@ impl Request {
             // Client (Owner) to SrcElders
             InsAuthKey { .. } |
             DelAuthKey { .. } => Type::Mutation,
+
+            // Batch
+
+            Batch { ref requests, .. } => requests
+                .iter()
+                .map(Request::get_type)
+                .max()
+                .unwrap_or(Type::PublicGet),
+        }
+    }
+
+    /// Returns `true` if this request must be signed by the requester, i.e. if it is a mutation or
+    /// a transaction. Read-only requests may be sent unsigned.
+    pub fn requires_signature(&self) -> bool {
+        match self.get_type() {
+            Type::PublicGet | Type::PrivateGet => false,
+            Type::Mutation | Type::Transaction => true,
+        }
+    }
+
+    /// Returns `true` if only the owners of the data this request targets may make it, i.e. if it
+    /// deletes ImmutableData or MutableData, or changes MutableData permissions. See
+    /// `Message::verify`.
+    ///
+    /// Batches return `false`, as their requests may target data with different owners, so only
+    /// requesters who are owners on their own can make such requests in a batch.
+    pub fn requires_owners_approval(&self) -> bool {
+        matches!(
+            self,
+            Request::DeleteUnpubIData(_)
+                | Request::DeleteMData(_)
+                | Request::SetMDataUserPermissions { .. }
+                | Request::DelMDataUserPermissions { .. }
+        )
+    }
+
+    /// Returns the address of the data this request targets, or `None` if it doesn't target any
+    /// single piece of data, as for coin, login packet and auth key requests, and batches.
+    pub fn dst_address(&self) -> Option<DataAddress> {
+        use Request::*;
+
+        match *self {
+            // IData
+            PutIData(ref data) => Some((*data.address()).into()),
+            GetIData(address) | DeleteUnpubIData(address) => Some(address.into()),
+            // MData
+            PutMData(ref data) => Some((*data.address()).into()),
+            GetMData(address)
+            | GetMDataValue { address, .. }
+            | DeleteMData(address)
+            | GetMDataShell(address)
+            | GetMDataVersion(address)
+            | ListMDataEntries(address)
+            | ListMDataKeys(address)
+            | ListMDataValues(address)
+            | SetMDataUserPermissions { address, .. }
+            | DelMDataUserPermissions { address, .. }
+            | ListMDataPermissions(address)
+            | ListMDataUserPermissions { address, .. }
+            | MutateMDataEntries { address, .. }
+            | ListMDataEntriesPage { address, .. }
+            | ListMDataKeysPage { address, .. }
+            | ListMDataValuesPage { address, .. }
+            | GetMDataIfNewer { address, .. }
+            | GetMDataEntryHistory { address, .. }
+            | GetMDataValueAt { address, .. } => Some(address.into()),
+            // AData
+            PutAData(ref data) => Some((*data.address()).into()),
+            GetAData(address)
+            | GetADataShell { address, .. }
+            | DeleteAData(address)
+            | GetADataRange { address, .. }
+            | GetADataValue { address, .. }
+            | GetADataIndices(address)
+            | GetADataLastEntry(address)
+            | GetADataPermissions { address, .. }
+            | GetPubADataUserPermissions { address, .. }
+            | GetUnpubADataUserPermissions { address, .. }
+            | GetADataOwners { address, .. }
+            | AddPubADataPermissions { address, .. }
+            | AddUnpubADataPermissions { address, .. }
+            | SetADataOwner { address, .. }
+            | GetADataRangePage { address, .. }
+            | GetADataIfNewer { address, .. } => Some(address.into()),
+            AppendSeq { ref append, .. } | AppendUnseq(ref append) => Some(append.address.into()),
+            // Subscriptions
+            Subscribe(target) | Unsubscribe(target) => Some(target.into()),
+            // Coins
+            TransferCoins { .. } | GetBalance | CreateBalance { .. } |
+            // Login Packet
+            CreateLoginPacket(_) | CreateLoginPacketFor { .. } | UpdateLoginPacket(_) |
+            GetLoginPacket(_) |
+            // Client (Owner) to SrcElders
+            ListAuthKeysAndVersion | InsAuthKey { .. } | DelAuthKey { .. } |
+            // Batch
+            Batch { .. } => None,
+        }
+    }
+
+    /// Returns the number of bytes this request stores on the network if it succeeds.
+    ///
+    /// This is the serialised size of the data, permissions, owner, login packet or auth key it
+    /// adds, or the total size of the keys and values of the entries it inserts, updates or
+    /// appends. It's zero for reads, deletions and coin operations.
+    pub fn storage_delta(&self) -> u64 {
+        use Request::*;
+
+        match *self {
+            // IData
+            PutIData(ref data) => utils::serialised_size(data),
+            // MData
+            PutMData(ref data) => utils::serialised_size(data),
+            SetMDataUserPermissions {
+                ref user,
+                ref permissions,
+                ..
+            } => utils::serialised_size(&(user, permissions)),
+            MutateMDataEntries { ref actions, .. } => entry_actions_size(actions),
+            // AData
+            PutAData(ref data) => utils::serialised_size(data),
+            AddPubADataPermissions {
+                ref permissions, ..
+            } => utils::serialised_size(permissions),
+            AddUnpubADataPermissions {
+                ref permissions, ..
+            } => utils::serialised_size(permissions),
+            SetADataOwner { ref owner, .. } => utils::serialised_size(owner),
+            AppendSeq { ref append, .. } | AppendUnseq(ref append) => append
+                .values
+                .iter()
+                .map(|entry| (entry.key.len() + entry.value.len()) as u64)
+                .fold(0, u64::saturating_add),
+            // Login Packet
+            CreateLoginPacket(ref login_packet)
+            | UpdateLoginPacket(ref login_packet)
+            | CreateLoginPacketFor {
+                new_login_packet: ref login_packet,
+                ..
+            } => utils::serialised_size(login_packet),
+            // Client (Owner) to SrcElders
+            InsAuthKey {
+                ref key,
+                ref permissions,
+                ..
+            } => utils::serialised_size(&(key, permissions)),
+            // Batch
+            Batch { ref requests, .. } => requests
+                .iter()
+                .map(Request::storage_delta)
+                .fold(0, u64::saturating_add),
+
+            // IData
+            GetIData(_) | DeleteUnpubIData(_) |
+            // MData
+            GetMData(_) | GetMDataValue { .. } | DeleteMData(_) | GetMDataShell(_) |
+            GetMDataVersion(_) | ListMDataEntries(_) | ListMDataKeys(_) | ListMDataValues(_) |
+            DelMDataUserPermissions { .. } | ListMDataPermissions(_) |
+            ListMDataUserPermissions { .. } | ListMDataEntriesPage { .. } |
+            ListMDataKeysPage { .. } | ListMDataValuesPage { .. } | GetMDataIfNewer { .. } |
+            GetMDataEntryHistory { .. } | GetMDataValueAt { .. } |
+            // AData
+            GetAData(_) | GetADataShell { .. } | DeleteAData(_) | GetADataRange { .. } |
+            GetADataValue { .. } | GetADataIndices(_) | GetADataLastEntry(_) |
+            GetADataPermissions { .. } | GetPubADataUserPermissions { .. } |
+            GetUnpubADataUserPermissions { .. } | GetADataOwners { .. } |
+            GetADataRangePage { .. } | GetADataIfNewer { .. } |
+            // Coins
+            TransferCoins { .. } | GetBalance | CreateBalance { .. } |
+            // Login Packet
+            GetLoginPacket(_) |
+            // Client (Owner) to SrcElders
+            ListAuthKeysAndVersion | DelAuthKey { .. } |
+            // Subscriptions
+            Subscribe(_) | Unsubscribe(_) => 0,
         }
     }
 
+    /// Checks that this request is well-formed, i.e. that a batch contains no batches.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if it isn't.
+    pub fn validate(&self) -> crate::Result<()> {
+        if let Request::Batch { ref requests, .. } = *self {
+            let nested = requests
+                .iter()
+                .any(|request| matches!(request, Request::Batch { .. }));
+            if nested {
+                return Err(Error::InvalidOperation);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the price of this request under `policy`.
+    ///
+    /// Reads are free. Mutations and transactions are charged per request and per byte of their
+    /// `storage_delta()`, not including any coins they transfer. A batch costs the sum of the
+    /// requests in it.
+    pub fn cost(&self, policy: &PricingPolicy) -> Coins {
+        if let Request::Batch { ref requests, .. } = *self {
+            let total = requests
+                .iter()
+                .map(|request| request.cost(policy).as_nano())
+                .fold(0, u64::saturating_add);
+            return Coins::from_nano(total);
+        }
+
+        match self.get_type() {
+            Type::PublicGet | Type::PrivateGet => Coins::from_nano(0),
+            Type::Mutation | Type::Transaction => policy.price(self.storage_delta()),
+        }
+    }
+
+    /// Returns `true` if `response` is the `Response` variant corresponding to this `Request`
+    /// variant, regardless of whether it holds a success or an error.
+    pub fn expects(&self, response: &Response) -> bool {
+        use Request::*;
+
+        let kind = match *self {
+            // IData
+            GetIData(_) => ResponseKind::GetIData,
+            // MData
+            GetMData(_) => ResponseKind::GetMData,
+            GetMDataValue { .. } => ResponseKind::GetMDataValue,
+            GetMDataShell(_) => ResponseKind::GetMDataShell,
+            GetMDataVersion(_) => ResponseKind::GetMDataVersion,
+            ListMDataEntries(_) => ResponseKind::ListMDataEntries,
+            ListMDataKeys(_) => ResponseKind::ListMDataKeys,
+            ListMDataValues(_) => ResponseKind::ListMDataValues,
+            ListMDataPermissions(_) => ResponseKind::ListMDataPermissions,
+            ListMDataUserPermissions { .. } => ResponseKind::ListMDataUserPermissions,
+            // AData
+            GetAData(_) => ResponseKind::GetAData,
+            GetADataShell { .. } => ResponseKind::GetADataShell,
+            GetADataValue { .. } => ResponseKind::GetADataValue,
+            GetADataRange { .. } => ResponseKind::GetADataRange,
+            GetADataIndices(_) => ResponseKind::GetADataIndices,
+            GetADataLastEntry(_) => ResponseKind::GetADataLastEntry,
+            GetADataPermissions { .. } => ResponseKind::GetADataPermissions,
+            GetPubADataUserPermissions { .. } => ResponseKind::GetPubADataUserPermissions,
+            GetUnpubADataUserPermissions { .. } => ResponseKind::GetUnpubADataUserPermissions,
+            GetADataOwners { .. } => ResponseKind::GetADataOwners,
+            // Paginated listings
+            ListMDataEntriesPage { .. } => ResponseKind::ListMDataEntriesPage,
+            ListMDataKeysPage { .. } => ResponseKind::ListMDataKeysPage,
+            ListMDataValuesPage { .. } => ResponseKind::ListMDataValuesPage,
+            GetADataRangePage { .. } => ResponseKind::GetADataRangePage,
+            // Conditional gets
+            GetMDataIfNewer { .. } => ResponseKind::GetMDataIfNewer,
+            GetADataIfNewer { .. } => ResponseKind::GetADataIfNewer,
+            // Subscriptions
+            Subscribe(_) | Unsubscribe(_) => ResponseKind::Subscription,
+            // MData history
+            GetMDataEntryHistory { .. } => ResponseKind::GetMDataEntryHistory,
+            GetMDataValueAt { .. } => ResponseKind::GetMDataValueAt,
+            // Batch
+            Batch { ref requests, .. } => {
+                return match response {
+                    Response::Batch(responses) => {
+                        requests.len() == responses.len()
+                            && requests
+                                .iter()
+                                .zip(responses)
+                                .all(|(request, response)| request.expects(response))
+                    }
+                    _ => false,
+                };
+            }
+            // Coins
+            GetBalance => ResponseKind::GetBalance,
+            // Login Packet
+            GetLoginPacket(..) => ResponseKind::GetLoginPacket,
+            // Client (Owner) to SrcElders
+            ListAuthKeysAndVersion => ResponseKind::ListAuthKeysAndVersion,
+
+            // Transaction
+
+            // Coins
+            TransferCoins { .. } | CreateBalance { .. }
+            // Login Packet
+            | CreateLoginPacketFor { .. } => ResponseKind::Transaction,
+
+            // Mutation
+
+            // IData
+            PutIData(_) |
+            DeleteUnpubIData(_) |
+            // MData
+            PutMData(_) |
+            DeleteMData(_) |
+            SetMDataUserPermissions { .. } |
+            DelMDataUserPermissions { .. } |
+            MutateMDataEntries { .. } |
+            // AData
+            PutAData(_) |
+            DeleteAData(_) |
+            AddPubADataPermissions { .. } |
+            AddUnpubADataPermissions { .. } |
+            SetADataOwner { .. } |
+            AppendSeq { .. } |
+            AppendUnseq(_) |
+            // Login Packet
+            CreateLoginPacket { .. } |
+            UpdateLoginPacket { .. } |
+            // Client (Owner) to SrcElders
+            InsAuthKey { .. } |
+            DelAuthKey { .. } => ResponseKind::Mutation,
+        };
+
+        response.kind() == kind
+    }
+
     /// Creates a Response containing an error, with the Response variant corresponding to the
     /// Request variant.
     pub fn error_response(&self, error: Error) -> Response {
@@ -410,6 +878,26 @@ impl Request {
                 Response::GetUnpubADataUserPermissions(Err(error))
             }
             GetADataOwners { .. } => Response::GetADataOwners(Err(error)),
+            // Paginated listings
+            ListMDataEntriesPage { .. } => Response::ListMDataEntriesPage(Err(error)),
+            ListMDataKeysPage { .. } => Response::ListMDataKeysPage(Err(error)),
+            ListMDataValuesPage { .. } => Response::ListMDataValuesPage(Err(error)),
+            GetADataRangePage { .. } => Response::GetADataRangePage(Err(error)),
+            // Conditional gets
+            GetMDataIfNewer { .. } => Response::GetMDataIfNewer(Err(error)),
+            GetADataIfNewer { .. } => Response::GetADataIfNewer(Err(error)),
+            // Subscriptions
+            Subscribe(_) | Unsubscribe(_) => Response::Subscription(Err(error)),
+            // MData history
+            GetMDataEntryHistory { .. } => Response::GetMDataEntryHistory(Err(error)),
+            GetMDataValueAt { .. } => Response::GetMDataValueAt(Err(error)),
+            // Batch
+            Batch { ref requests, .. } => Response::Batch(
+                requests
+                    .iter()
+                    .map(|request| request.error_response(error.clone()))
+                    .collect(),
+            ),
             // Coins
             GetBalance => Response::GetBalance(Err(error)),
             // Login Packet
@@ -454,6 +942,124 @@ impl Request {
     }
 }
 
+fn entry_actions_size(actions: &MDataEntryActions) -> u64 {
+    let sizes: Vec<u64> = match *actions {
+        MDataEntryActions::Seq(ref actions) => actions
+            .actions()
+            .iter()
+            .map(|(key, action)| match *action {
+                MDataSeqEntryAction::Ins(ref value) | MDataSeqEntryAction::Update(ref value) => {
+                    (key.len() + value.data.len()) as u64
+                }
+                MDataSeqEntryAction::Del(_) => 0,
+            })
+            .collect(),
+        MDataEntryActions::Unseq(ref actions) => actions
+            .actions()
+            .iter()
+            .map(|(key, action)| match *action {
+                MDataUnseqEntryAction::Ins(ref value)
+                | MDataUnseqEntryAction::Update(ref value)
+                | MDataUnseqEntryAction::UpdateIfEquals { ref value, .. }
+                | MDataUnseqEntryAction::Upsert(ref value) => (key.len() + value.len()) as u64,
+                MDataUnseqEntryAction::Del | MDataUnseqEntryAction::DelIfEquals(_) => 0,
+            })
+            .collect(),
+    };
+    sizes.into_iter().fold(0, u64::saturating_add)
+}
+
+thread_local! {
+    // Whether a batch is being deserialised on this thread, so that a nested one is rejected
+    // before the decoder recurses any deeper.
+    static DESERIALISING_BATCH: Cell<bool> = const { Cell::new(false) };
+}
+
+fn deserialise_batch<'de, D: Deserializer<'de>>(deserialiser: D) -> Result<Vec<Request>, D::Error> {
+    if DESERIALISING_BATCH.with(Cell::get) {
+        return Err(D::Error::custom("batches can't be nested"));
+    }
+    DESERIALISING_BATCH.with(|flag| flag.set(true));
+    let requests = Vec::deserialize(deserialiser);
+    DESERIALISING_BATCH.with(|flag| flag.set(false));
+    requests
+}
+
+/// The requests that transform the old snapshot of an `MDataDiff` into the new: the entry actions
+/// followed by the permission changes, versioned successively from the old version.
+///
+/// If the permission changes don't account for every version between the snapshots, e.g. because
+/// a user's permissions were set more than once, the gap is closed with redundant
+/// `SetMDataUserPermissions` requests: before the changes for a removed user, or after them for a
+/// user whose new permissions are known.
+///
+/// Fails with `Error::InvalidOperation` if the owners changed, which has no corresponding request,
+/// or if there is a gap but no permission changes to pad it with.
+impl TryFrom<&MDataDiff> for Vec<Request> {
+    type Error = Error;
+
+    fn try_from(diff: &MDataDiff) -> Result<Self, Self::Error> {
+        if diff.owners.is_some() {
+            return Err(Error::InvalidOperation);
+        }
+        let gap = diff
+            .new_version
+            .checked_sub(diff.old_version)
+            .and_then(|versions| versions.checked_sub(diff.permissions.len() as u64))
+            .ok_or(Error::InvalidOperation)?;
+
+        let mut permissions: Vec<_> = diff
+            .permissions
+            .iter()
+            .map(|(user, permissions)| (*user, permissions.clone()))
+            .collect();
+        if gap > 0 {
+            match diff.permissions.iter().find(|(_, set)| set.is_some()) {
+                // Setting a user's new permissions again after the changes is redundant...
+                Some((user, set)) => {
+                    permissions.extend(iter::repeat_n((*user, set.clone()), gap as usize))
+                }
+                // ...as is setting permissions for a user whom the changes then remove.
+                None => {
+                    let user = *diff
+                        .permissions
+                        .keys()
+                        .next()
+                        .ok_or(Error::InvalidOperation)?;
+                    let padding = (user, Some(MDataPermissionSet::new()));
+                    let _ = permissions.splice(0..0, iter::repeat_n(padding, gap as usize));
+                }
+            }
+        }
+
+        let mut requests: Vec<_> = diff
+            .entry_actions()
+            .into_iter()
+            .map(|actions| Request::MutateMDataEntries {
+                address: diff.address,
+                actions,
+            })
+            .collect();
+        for (version, (user, permissions)) in (diff.old_version + 1..).zip(permissions) {
+            requests.push(match permissions {
+                Some(permissions) => Request::SetMDataUserPermissions {
+                    address: diff.address,
+                    user,
+                    permissions,
+                    version,
+                },
+                None => Request::DelMDataUserPermissions {
+                    address: diff.address,
+                    user,
+                    version,
+                },
+            });
+        }
+
+        Ok(requests)
+    }
+}
+
 impl fmt::Debug for Request {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         use Request::*;
@@ -512,7 +1118,621 @@ impl fmt::Debug for Request {
                 ListAuthKeysAndVersion => "ListAuthKeysAndVersion",
                 InsAuthKey { .. } => "InsAuthKey",
                 DelAuthKey { .. } => "DelAuthKey",
+                // Paginated listings
+                ListMDataEntriesPage { .. } => "ListMDataEntriesPage",
+                ListMDataKeysPage { .. } => "ListMDataKeysPage",
+                ListMDataValuesPage { .. } => "ListMDataValuesPage",
+                GetADataRangePage { .. } => "GetADataRangePage",
+                // Batch
+                Batch { .. } => "Batch",
+                // Conditional gets
+                GetMDataIfNewer { .. } => "GetMDataIfNewer",
+                GetADataIfNewer { .. } => "GetADataIfNewer",
+                // Subscriptions
+                Subscribe(_) => "Subscribe",
+                Unsubscribe(_) => "Unsubscribe",
+                // MData history
+                GetMDataEntryHistory { .. } => "GetMDataEntryHistory",
+                GetMDataValueAt { .. } => "GetMDataValueAt",
             }
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        ADataEntry, Keypair, MDataAction, MDataSeqEntryActions, PubImmutableData,
+        PubSeqAppendOnlyData, SeqMutableData,
+    };
+    use std::collections::BTreeMap;
+    use unwrap::unwrap;
+
+    // One instance of every `Request` variant.
+    fn all_requests() -> Vec<Request> {
+        let keypair = Keypair::new_ed25519(&mut rand::thread_rng());
+        let key = keypair.public_key();
+        let name = XorName([1; 32]);
+        let idata_address = IDataAddress::Pub(name);
+        let mdata_address = MDataAddress::Seq { name, tag: 15000 };
+        let adata_address = ADataAddress::PubSeq { name, tag: 10000 };
+        let append = ADataAppendOperation {
+            address: adata_address,
+            values: vec![ADataEntry::new(b"key".to_vec(), b"value".to_vec())],
+        };
+        let login_packet = unwrap!(LoginPacket::new(
+            name,
+            key,
+            b"data".to_vec(),
+            keypair.sign(b"data")
+        ));
+        let mdata_cursor = MDataCursor {
+            start: None,
+            limit: 10,
+        };
+        let app_permissions = AppPermissions {
+            transfer_coins: true,
+            perform_mutations: true,
+            get_balance: true,
+        };
+
+        vec![
+            // IData
+            Request::PutIData(PubImmutableData::new(b"value".to_vec()).into()),
+            Request::GetIData(idata_address),
+            Request::DeleteUnpubIData(idata_address),
+            // MData
+            Request::PutMData(SeqMutableData::new(name, 15000, key).into()),
+            Request::GetMData(mdata_address),
+            Request::GetMDataValue {
+                address: mdata_address,
+                key: b"key".to_vec(),
+            },
+            Request::DeleteMData(mdata_address),
+            Request::GetMDataShell(mdata_address),
+            Request::GetMDataVersion(mdata_address),
+            Request::ListMDataEntries(mdata_address),
+            Request::ListMDataKeys(mdata_address),
+            Request::ListMDataValues(mdata_address),
+            Request::SetMDataUserPermissions {
+                address: mdata_address,
+                user: key,
+                permissions: MDataPermissionSet::new(),
+                version: 1,
+            },
+            Request::DelMDataUserPermissions {
+                address: mdata_address,
+                user: key,
+                version: 1,
+            },
+            Request::ListMDataPermissions(mdata_address),
+            Request::ListMDataUserPermissions {
+                address: mdata_address,
+                user: key,
+            },
+            Request::MutateMDataEntries {
+                address: mdata_address,
+                actions: MDataSeqEntryActions::new()
+                    .ins(b"key".to_vec(), b"value".to_vec(), 0)
+                    .into(),
+            },
+            // AData
+            Request::PutAData(PubSeqAppendOnlyData::new(name, 10000).into()),
+            Request::GetAData(adata_address),
+            Request::GetADataShell {
+                address: adata_address,
+                data_index: ADataIndex::FromEnd(0),
+            },
+            Request::DeleteAData(adata_address),
+            Request::GetADataRange {
+                address: adata_address,
+                range: (ADataIndex::FromStart(0), ADataIndex::FromEnd(0)),
+            },
+            Request::GetADataValue {
+                address: adata_address,
+                key: b"key".to_vec(),
+            },
+            Request::GetADataIndices(adata_address),
+            Request::GetADataLastEntry(adata_address),
+            Request::GetADataPermissions {
+                address: adata_address,
+                permissions_index: ADataIndex::FromEnd(0),
+            },
+            Request::GetPubADataUserPermissions {
+                address: adata_address,
+                permissions_index: ADataIndex::FromEnd(0),
+                user: ADataUser::Anyone,
+            },
+            Request::GetUnpubADataUserPermissions {
+                address: adata_address,
+                permissions_index: ADataIndex::FromEnd(0),
+                public_key: key,
+            },
+            Request::GetADataOwners {
+                address: adata_address,
+                owners_index: ADataIndex::FromEnd(0),
+            },
+            Request::AddPubADataPermissions {
+                address: adata_address,
+                permissions: ADataPubPermissions {
+                    permissions: BTreeMap::new(),
+                    entries_index: 0,
+                    owners_index: 0,
+                },
+                permissions_index: 0,
+            },
+            Request::AddUnpubADataPermissions {
+                address: adata_address,
+                permissions: ADataUnpubPermissions {
+                    permissions: BTreeMap::new(),
+                    entries_index: 0,
+                    owners_index: 0,
+                },
+                permissions_index: 0,
+            },
+            Request::SetADataOwner {
+                address: adata_address,
+                owner: ADataOwner {
+                    public_key: key,
+                    entries_index: 0,
+                    permissions_index: 0,
+                },
+                owners_index: 0,
+            },
+            Request::AppendSeq {
+                append: append.clone(),
+                index: 0,
+            },
+            Request::AppendUnseq(append),
+            // Coins
+            Request::TransferCoins {
+                destination: name,
+                amount: Coins::from_nano(1),
+                transaction_id: 1,
+            },
+            Request::GetBalance,
+            Request::CreateBalance {
+                new_balance_owner: key,
+                amount: Coins::from_nano(1),
+                transaction_id: 1,
+            },
+            // Login Packet
+            Request::CreateLoginPacket(login_packet.clone()),
+            Request::CreateLoginPacketFor {
+                new_owner: key,
+                amount: Coins::from_nano(1),
+                transaction_id: 1,
+                new_login_packet: login_packet.clone(),
+            },
+            Request::UpdateLoginPacket(login_packet),
+            Request::GetLoginPacket(name),
+            // Client (Owner) to SrcElders
+            Request::ListAuthKeysAndVersion,
+            Request::InsAuthKey {
+                key,
+                version: 1,
+                permissions: app_permissions,
+            },
+            Request::DelAuthKey { key, version: 1 },
+            // Paginated listings
+            Request::ListMDataEntriesPage {
+                address: mdata_address,
+                cursor: mdata_cursor.clone(),
+            },
+            Request::ListMDataKeysPage {
+                address: mdata_address,
+                cursor: mdata_cursor.clone(),
+            },
+            Request::ListMDataValuesPage {
+                address: mdata_address,
+                cursor: mdata_cursor,
+            },
+            Request::GetADataRangePage {
+                address: adata_address,
+                cursor: ADataCursor {
+                    start: ADataIndex::FromStart(0),
+                    limit: 10,
+                },
+            },
+            // Batch
+            Request::Batch {
+                requests: vec![Request::GetBalance, Request::GetIData(idata_address)],
+                mode: BatchMode::Atomic,
+            },
+            // Conditional gets
+            Request::GetMDataIfNewer {
+                address: mdata_address,
+                known_version: 1,
+            },
+            Request::GetADataIfNewer {
+                address: adata_address,
+                known_indices: ADataIndices::new(1, 1, 1),
+            },
+            // Subscriptions
+            Request::Subscribe(SubscriptionTarget::MData(mdata_address)),
+            Request::Unsubscribe(SubscriptionTarget::AData(adata_address)),
+            // MData history
+            Request::GetMDataEntryHistory {
+                address: mdata_address,
+                key: vec![1],
+            },
+            Request::GetMDataValueAt {
+                address: mdata_address,
+                key: vec![1],
+                version: 2,
+            },
+        ]
+    }
+
+    fn error<T>() -> crate::Result<T> {
+        Err(Error::NoSuchData)
+    }
+
+    // One instance of every `Response` variant.
+    fn all_responses() -> Vec<Response> {
+        vec![
+            // IData
+            Response::GetIData(error()),
+            // MData
+            Response::GetMData(error()),
+            Response::GetMDataShell(error()),
+            Response::GetMDataVersion(error()),
+            Response::ListMDataEntries(error()),
+            Response::ListMDataKeys(error()),
+            Response::ListMDataValues(error()),
+            Response::ListMDataUserPermissions(error()),
+            Response::ListMDataPermissions(error()),
+            Response::GetMDataValue(error()),
+            // AData
+            Response::GetAData(error()),
+            Response::GetADataShell(error()),
+            Response::GetADataOwners(error()),
+            Response::GetADataRange(error()),
+            Response::GetADataValue(error()),
+            Response::GetADataIndices(error()),
+            Response::GetADataLastEntry(error()),
+            Response::GetADataPermissions(error()),
+            Response::GetPubADataUserPermissions(error()),
+            Response::GetUnpubADataUserPermissions(error()),
+            // Coins
+            Response::GetBalance(error()),
+            Response::Transaction(error()),
+            // Login Packet
+            Response::GetLoginPacket(error()),
+            // Client (Owner) to SrcElders
+            Response::ListAuthKeysAndVersion(error()),
+            // Mutation
+            Response::Mutation(error()),
+            // Paginated listings
+            Response::ListMDataEntriesPage(error()),
+            Response::ListMDataKeysPage(error()),
+            Response::ListMDataValuesPage(error()),
+            Response::GetADataRangePage(error()),
+            // Batch
+            Response::Batch(vec![
+                Response::GetBalance(error()),
+                Response::GetIData(error()),
+            ]),
+            // Conditional gets
+            Response::GetMDataIfNewer(error()),
+            Response::GetADataIfNewer(error()),
+            // Subscriptions
+            Response::Subscription(error()),
+            // MData history
+            Response::GetMDataEntryHistory(error()),
+            Response::GetMDataValueAt(error()),
+        ]
+    }
+
+    #[test]
+    fn each_request_expects_exactly_one_response_variant() {
+        let responses = all_responses();
+
+        for request in all_requests() {
+            let expected: Vec<_> = responses
+                .iter()
+                .filter(|response| request.expects(response))
+                .collect();
+            assert_eq!(
+                expected,
+                vec![&request.error_response(Error::NoSuchData)],
+                "{:?}",
+                request
+            );
+        }
+    }
+
+    #[test]
+    fn each_response_variant_is_expected_by_some_request() {
+        let requests = all_requests();
+
+        for response in all_responses() {
+            assert!(
+                requests.iter().any(|request| request.expects(&response)),
+                "{:?}",
+                response
+            );
+        }
+    }
+
+    #[test]
+    fn expects_success_and_error() {
+        let request = Request::GetMData(MDataAddress::Seq {
+            name: XorName([1; 32]),
+            tag: 15000,
+        });
+        let owner = Keypair::new_ed25519(&mut rand::thread_rng()).public_key();
+        let data: MData = SeqMutableData::new(XorName([1; 32]), 15000, owner).into();
+
+        assert!(request.expects(&Response::GetMData(Ok(data.clone()))));
+        assert!(request.expects(&Response::GetMData(Err(Error::AccessDenied))));
+        assert!(!request.expects(&Response::GetMDataShell(Ok(data))));
+        assert!(!request.expects(&Response::Mutation(Ok(()))));
+    }
+
+    #[test]
+    fn batch_type() {
+        let batch = |requests| Request::Batch {
+            requests,
+            mode: BatchMode::BestEffort,
+        };
+        let pub_get = Request::GetIData(IDataAddress::Pub(XorName([1; 32])));
+        let unpub_get = Request::GetIData(IDataAddress::Unpub(XorName([1; 32])));
+        let mutation = Request::DeleteUnpubIData(IDataAddress::Unpub(XorName([1; 32])));
+        let transaction = Request::TransferCoins {
+            destination: XorName([2; 32]),
+            amount: Coins::from_nano(1),
+            transaction_id: 1,
+        };
+
+        assert_eq!(batch(vec![]).get_type(), Type::PublicGet);
+        assert_eq!(batch(vec![pub_get.clone()]).get_type(), Type::PublicGet);
+        assert_eq!(
+            batch(vec![pub_get.clone(), unpub_get.clone()]).get_type(),
+            Type::PrivateGet
+        );
+        assert!(!batch(vec![pub_get.clone(), unpub_get.clone()]).requires_signature());
+
+        let batch_with_mutation = batch(vec![unpub_get, mutation.clone(), pub_get]);
+        assert_eq!(batch_with_mutation.get_type(), Type::Mutation);
+        assert!(batch_with_mutation.requires_signature());
+        assert_eq!(
+            batch(vec![transaction, mutation]).get_type(),
+            Type::Transaction
+        );
+    }
+
+    #[test]
+    fn batch_expects_responses_in_order() {
+        let request = Request::Batch {
+            requests: vec![
+                Request::GetBalance,
+                Request::DelAuthKey {
+                    key: Keypair::new_ed25519(&mut rand::thread_rng()).public_key(),
+                    version: 1,
+                },
+            ],
+            mode: BatchMode::Atomic,
+        };
+        let balance = Response::GetBalance(Ok(Coins::from_nano(1)));
+        let mutation = Response::Mutation(Err(Error::BatchAborted));
+
+        assert!(request.expects(&Response::Batch(vec![balance.clone(), mutation.clone()])));
+        assert!(!request.expects(&Response::Batch(vec![mutation.clone(), balance.clone()])));
+        assert!(!request.expects(&Response::Batch(vec![balance.clone()])));
+        assert!(!request.expects(&Response::Batch(vec![balance, mutation.clone(), mutation])));
+        assert!(!request.expects(&Response::GetBalance(Err(Error::AccessDenied))));
+
+        assert_eq!(
+            request.error_response(Error::InvalidSignature),
+            Response::Batch(vec![
+                Response::GetBalance(Err(Error::InvalidSignature)),
+                Response::Mutation(Err(Error::InvalidSignature)),
+            ])
+        );
+    }
+
+    #[test]
+    fn subscription_type() {
+        let name = XorName([1; 32]);
+        let mdata = SubscriptionTarget::MData(MDataAddress::Unseq { name, tag: 15000 });
+        let pub_adata = SubscriptionTarget::AData(ADataAddress::PubSeq { name, tag: 10000 });
+        let unpub_adata = SubscriptionTarget::AData(ADataAddress::UnpubSeq { name, tag: 10000 });
+
+        assert_eq!(Request::Subscribe(mdata).get_type(), Type::PrivateGet);
+        assert_eq!(Request::Subscribe(pub_adata).get_type(), Type::PublicGet);
+        assert_eq!(
+            Request::Unsubscribe(unpub_adata).get_type(),
+            Type::PrivateGet
+        );
+        assert!(!Request::Unsubscribe(pub_adata).requires_signature());
+    }
+
+    #[test]
+    fn dst_address() {
+        let name = XorName([1; 32]);
+        let mdata_address = MDataAddress::Seq { name, tag: 15000 };
+        let adata_address = ADataAddress::PubSeq { name, tag: 10000 };
+        let idata = IData::from(PubImmutableData::new(b"value".to_vec()));
+
+        assert_eq!(
+            Request::PutIData(idata.clone()).dst_address(),
+            Some(DataAddress::Immutable(*idata.address()))
+        );
+        assert_eq!(
+            Request::ListMDataKeys(mdata_address).dst_address(),
+            Some(DataAddress::Mutable(mdata_address))
+        );
+        assert_eq!(
+            Request::AppendUnseq(ADataAppendOperation {
+                address: adata_address,
+                values: vec![],
+            })
+            .dst_address(),
+            Some(DataAddress::AppendOnly(adata_address))
+        );
+        assert_eq!(
+            Request::Subscribe(SubscriptionTarget::MData(mdata_address)).dst_address(),
+            Some(DataAddress::Mutable(mdata_address))
+        );
+        assert_eq!(Request::GetBalance.dst_address(), None);
+        assert_eq!(
+            Request::Batch {
+                requests: vec![Request::ListMDataKeys(mdata_address)],
+                mode: BatchMode::BestEffort,
+            }
+            .dst_address(),
+            None
+        );
+    }
+
+    #[test]
+    fn nested_batch() {
+        let mdata_address = MDataAddress::Seq {
+            name: XorName([1; 32]),
+            tag: 15000,
+        };
+        let batch = |requests| Request::Batch {
+            requests,
+            mode: BatchMode::Atomic,
+        };
+
+        let flat = batch(vec![Request::ListMDataKeys(mdata_address)]);
+        assert_eq!(flat.validate(), Ok(()));
+        assert_eq!(Request::GetBalance.validate(), Ok(()));
+        let nested = batch(vec![Request::GetBalance, flat.clone()]);
+        assert_eq!(nested.validate(), Err(Error::InvalidOperation));
+
+        // A nested batch doesn't even deserialise.
+        let serialised = utils::serialise(&flat);
+        assert_eq!(unwrap!(bincode::deserialize::<Request>(&serialised)), flat);
+        assert!(bincode::deserialize::<Request>(&utils::serialise(&nested)).is_err());
+        // ...and the failure doesn't affect later batches.
+        assert_eq!(unwrap!(bincode::deserialize::<Request>(&serialised)), flat);
+    }
+
+    #[test]
+    fn storage_delta_and_cost() {
+        let name = XorName([1; 32]);
+        let mdata_address = MDataAddress::Seq { name, tag: 15000 };
+        let policy = PricingPolicy {
+            per_request: Coins::from_nano(1000),
+            per_byte: Coins::from_nano(2),
+        };
+
+        let put = Request::PutIData(PubImmutableData::new(vec![0; 100]).into());
+        assert!(put.storage_delta() > 100);
+        assert_eq!(
+            put.cost(&policy),
+            Coins::from_nano(1000 + 2 * put.storage_delta())
+        );
+
+        // Only inserted and updated entries count.
+        let mutate = Request::MutateMDataEntries {
+            address: mdata_address,
+            actions: MDataSeqEntryActions::new()
+                .ins(b"key".to_vec(), b"value".to_vec(), 0)
+                .update(b"other".to_vec(), b"val".to_vec(), 1)
+                .del(b"gone".to_vec(), 1)
+                .into(),
+        };
+        assert_eq!(mutate.storage_delta(), 16);
+        assert_eq!(mutate.cost(&policy), Coins::from_nano(1032));
+
+        // Deletions store nothing, but are still charged per request.
+        let delete = Request::DeleteMData(mdata_address);
+        assert_eq!(delete.storage_delta(), 0);
+        assert_eq!(delete.cost(&policy), Coins::from_nano(1000));
+
+        // Reads are free.
+        let get = Request::ListMDataEntries(mdata_address);
+        assert_eq!(get.storage_delta(), 0);
+        assert_eq!(get.cost(&policy), Coins::from_nano(0));
+
+        let batch = Request::Batch {
+            requests: vec![get, mutate.clone(), delete],
+            mode: BatchMode::Atomic,
+        };
+        assert_eq!(batch.storage_delta(), 16);
+        assert_eq!(batch.cost(&policy), Coins::from_nano(2032));
+    }
+
+    #[test]
+    fn mdata_diff_requests() {
+        let owner = Keypair::new_ed25519(&mut rand::thread_rng()).public_key();
+        let user = Keypair::new_ed25519(&mut rand::thread_rng()).public_key();
+        let mut old = SeqMutableData::new(XorName([1; 32]), 10000, owner);
+        unwrap!(old.set_user_permissions(user, MDataPermissionSet::new(), 1));
+        unwrap!(old.mutate_entries(
+            MDataSeqEntryActions::new()
+                .ins(b"a".to_vec(), b"1".to_vec(), 0)
+                .ins(b"b".to_vec(), b"1".to_vec(), 0),
+            owner
+        ));
+        let old = MData::from(old);
+
+        let mut new = old.clone();
+        unwrap!(new.mutate_entries(
+            MDataSeqEntryActions::new()
+                .update(b"a".to_vec(), b"2".to_vec(), 1)
+                .del(b"b".to_vec(), 1)
+                .into(),
+            owner
+        ));
+        let mut permissions = new.clone();
+        unwrap!(new.del_user_permissions(user, 2));
+
+        // Replaying the requests onto a copy of the old snapshot gives the new one.
+        let replay = |new: &MData| {
+            let diff = unwrap!(MData::diff(&old, new));
+            let mut copy = old.clone();
+            for request in unwrap!(Vec::<Request>::try_from(&diff)) {
+                match request {
+                    Request::MutateMDataEntries { actions, .. } => {
+                        unwrap!(copy.mutate_entries(actions, owner))
+                    }
+                    Request::SetMDataUserPermissions {
+                        user,
+                        permissions,
+                        version,
+                        ..
+                    } => unwrap!(copy.set_user_permissions(user, permissions, version)),
+                    Request::DelMDataUserPermissions { user, version, .. } => {
+                        unwrap!(copy.del_user_permissions(user, version))
+                    }
+                    request => panic!("Unexpected request: {:?}", request),
+                }
+            }
+            assert_eq!(copy, *new);
+        };
+        replay(&new);
+
+        // Versions of permissions which were set more than once are padded, with the removed
+        // user's...
+        let mut removed = new.clone();
+        unwrap!(removed.set_user_permissions(user, MDataPermissionSet::new(), 3));
+        unwrap!(removed.del_user_permissions(user, 4));
+        replay(&removed);
+
+        // ...or the changed user's permissions.
+        let other = Keypair::new_ed25519(&mut rand::thread_rng()).public_key();
+        let insert = MDataPermissionSet::new().allow(MDataAction::Insert);
+        unwrap!(permissions.set_user_permissions(other, MDataPermissionSet::new(), 2));
+        unwrap!(permissions.set_user_permissions(other, insert, 3));
+        replay(&permissions);
+
+        // A change of owners can't be replayed.
+        let new_owner = Keypair::new_ed25519(&mut rand::thread_rng()).public_key();
+        let mut new = match new {
+            MData::Seq(data) => data,
+            MData::Unseq(_) => unreachable!(),
+        };
+        unwrap!(new.change_owner(new_owner, 3));
+        let diff = unwrap!(MData::diff(&old, &MData::from(new)));
+        assert_eq!(
+            Vec::<Request>::try_from(&diff),
+            Err(Error::InvalidOperation)
+        );
+    }
+}
@@ -78,7 +78,6 @@ impl LoginPacket {
 mod tests {
     use super::{LoginPacket, MAX_LOGIN_PACKET_BYTES};
     use crate::{ClientFullId, Error};
-    use rand;
 
     #[test]
     fn exceed_size_limit() {
@@ -0,0 +1,223 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+use crate::{utils, Approvals, Message, MessageId, Request, Result, Signer};
+
+/// Builder for a `Message::Request`.
+///
+/// The request is assigned a fresh `MessageId` and is signed by the given signer only if
+/// `Request::requires_signature` says so, i.e. read-only requests are sent unsigned.
+pub struct SignedRequest<'a, S: Signer + ?Sized> {
+    request: Request,
+    message_id: MessageId,
+    signer: &'a S,
+    approvals: Option<Approvals>,
+}
+
+impl<'a, S: Signer + ?Sized> SignedRequest<'a, S> {
+    /// Constructs a new builder for `request`, using a random `MessageId`.
+    pub fn new(request: Request, signer: &'a S) -> Self {
+        Self {
+            request,
+            message_id: MessageId::new(),
+            signer,
+            approvals: None,
+        }
+    }
+
+    /// Replaces the generated `MessageId` with the given one.
+    pub fn message_id(mut self, message_id: MessageId) -> Self {
+        self.message_id = message_id;
+        self
+    }
+
+    /// Attaches the owners' approvals of `(request, message_id)`, which must be made with the
+    /// final `MessageId`.
+    pub fn approvals(mut self, approvals: Approvals) -> Self {
+        self.approvals = Some(approvals);
+        self
+    }
+
+    /// Builds the `Message::Request`, signing `(request, message_id)` if required.
+    ///
+    /// Returns `Err(Error::InvalidOperation)` if the request fails `Request::validate`.
+    pub fn build(self) -> Result<Message> {
+        self.request.validate()?;
+        let signature = if self.request.requires_signature() {
+            let data = utils::serialise(&(&self.request, self.message_id));
+            Some(self.signer.sign(&data))
+        } else {
+            None
+        };
+
+        Ok(Message::Request {
+            request: self.request,
+            message_id: self.message_id,
+            signature,
+            approvals: self.approvals,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignedRequest;
+    use crate::{
+        utils, Approvals, BatchMode, ClientFullId, Error, Keypair, MDataAddress, Message,
+        MessageId, Owners, PublicId, Request, Signer,
+    };
+    use std::collections::BTreeMap;
+    use unwrap::unwrap;
+
+    fn gen_address() -> MDataAddress {
+        MDataAddress::Seq {
+            name: rand::random(),
+            tag: 15000,
+        }
+    }
+
+    #[test]
+    fn mutation_is_signed() {
+        let client_id = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let public_id = PublicId::Client(client_id.public_id().clone());
+        let message_id = MessageId::new();
+        let owners = Owners::Single(*client_id.public_id().public_key());
+
+        let message = unwrap!(
+            SignedRequest::new(Request::DeleteMData(gen_address()), &client_id)
+                .message_id(message_id)
+                .build()
+        );
+
+        match message {
+            Message::Request {
+                message_id: id,
+                signature: Some(_),
+                ..
+            } => assert_eq!(id, message_id),
+            _ => panic!("Unexpected message: {:?}", message),
+        }
+        unwrap!(message.verify(&public_id, Some(&owners)));
+
+        // Verification against a different requester must fail.
+        let other_id = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let other_public_id = PublicId::Client(other_id.public_id().clone());
+        assert_eq!(
+            message.verify(&other_public_id, Some(&owners)),
+            Err(Error::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn read_is_not_signed() {
+        let client_id = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let public_id = PublicId::Client(client_id.public_id().clone());
+
+        let message =
+            unwrap!(SignedRequest::new(Request::GetMData(gen_address()), &client_id).build());
+
+        match message {
+            Message::Request {
+                signature: None, ..
+            } => (),
+            _ => panic!("Unexpected message: {:?}", message),
+        }
+        unwrap!(message.verify(&public_id, None));
+    }
+
+    #[test]
+    fn unsigned_mutation_is_rejected() {
+        let client_id = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let public_id = PublicId::Client(client_id.public_id().clone());
+
+        let message = Message::Request {
+            request: Request::DeleteMData(gen_address()),
+            message_id: MessageId::new(),
+            signature: None,
+            approvals: None,
+        };
+        assert_eq!(
+            message.verify(&public_id, None),
+            Err(Error::InvalidSignature)
+        );
+    }
+
+    #[test]
+    fn nested_batch_is_rejected() {
+        let client_id = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let public_id = PublicId::Client(client_id.public_id().clone());
+        let batch = |requests| Request::Batch {
+            requests,
+            mode: BatchMode::Atomic,
+        };
+        let request = batch(vec![batch(vec![Request::GetMData(gen_address())])]);
+
+        assert_eq!(
+            SignedRequest::new(request.clone(), &client_id)
+                .build()
+                .map(|_| ()),
+            Err(Error::InvalidOperation)
+        );
+
+        let message = Message::Request {
+            request,
+            message_id: MessageId::new(),
+            signature: None,
+            approvals: None,
+        };
+        assert_eq!(
+            message.verify(&public_id, None),
+            Err(Error::InvalidOperation)
+        );
+    }
+
+    #[test]
+    fn owners_approval() {
+        let client_id = ClientFullId::new_ed25519(&mut rand::thread_rng());
+        let public_id = PublicId::Client(client_id.public_id().clone());
+        let other = Keypair::new_ed25519(&mut rand::thread_rng());
+        let keys = vec![*client_id.public_id().public_key(), other.public_key()];
+        let owners = unwrap!(Owners::threshold(keys.into_iter().collect(), 2));
+
+        let request = Request::DeleteMData(gen_address());
+        let message_id = MessageId::new();
+        let build = |approvals| {
+            let builder = SignedRequest::new(request.clone(), &client_id).message_id(message_id);
+            match approvals {
+                Some(approvals) => unwrap!(builder.approvals(approvals).build()),
+                None => unwrap!(builder.build()),
+            }
+        };
+
+        // The requester alone isn't enough.
+        let message = build(None);
+        assert_eq!(
+            message.verify(&public_id, Some(&owners)),
+            Err(Error::AccessDenied)
+        );
+        // The owners are needed to check the approval.
+        assert_eq!(
+            message.verify(&public_id, None),
+            Err(Error::InvalidOperation)
+        );
+
+        let data = utils::serialise(&(&request, message_id));
+        let mut signatures = BTreeMap::new();
+        let _ = signatures.insert(client_id.public_key(), client_id.sign(&data));
+        let message = build(Some(Approvals::Signatures(signatures.clone())));
+        assert_eq!(
+            message.verify(&public_id, Some(&owners)),
+            Err(Error::AccessDenied)
+        );
+
+        let _ = signatures.insert(other.public_key(), other.sign(&data));
+        let message = build(Some(Approvals::Signatures(signatures)));
+        unwrap!(message.verify(&public_id, Some(&owners)));
+    }
+}
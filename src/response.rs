@@ -8,10 +8,10 @@
 // Software.
 
 use crate::{
-    errors::ErrorDebug, AData, ADataEntries, ADataEntry, ADataIndices, ADataOwner,
+    errors::ErrorDebug, AData, ADataEntries, ADataEntry, ADataIndices, ADataOwner, ADataPage,
     ADataPermissions, ADataPubPermissionSet, ADataUnpubPermissionSet, AppPermissions, Coins, Error,
-    IData, MData, MDataEntries, MDataPermissionSet, MDataValue, MDataValues, PublicKey, Result,
-    Signature, Transaction,
+    IData, MData, MDataEntries, MDataPage, MDataPermissionSet, MDataRevision, MDataSeqValue,
+    MDataValue, MDataValues, PublicKey, Request, Result, Signature, Transaction,
 };
 use serde::{Deserialize, Serialize};
 use std::{
@@ -95,6 +95,196 @@ pub enum Response {
     //
     /// Return a success or failure status for a mutation operation.
     Mutation(Result<()>),
+    //
+    // ===== Paginated listings =====
+    //
+    /// A page of MutableData entries.
+    ListMDataEntriesPage(Result<MDataPage<MDataEntries>>),
+    /// A page of MutableData keys.
+    ListMDataKeysPage(Result<MDataPage<BTreeSet<Vec<u8>>>>),
+    /// A page of MutableData values.
+    ListMDataValuesPage(Result<MDataPage<MDataValues>>),
+    /// A page of AppendOnlyData entries.
+    GetADataRangePage(Result<ADataPage>),
+    //
+    // ===== Batch =====
+    //
+    /// Responses to the requests in a `Request::Batch`, in the same order.
+    Batch(Vec<Response>),
+    //
+    // ===== Conditional gets =====
+    //
+    /// Get MutableData if it's newer than the known version.
+    GetMDataIfNewer(Result<IfModified<MData>>),
+    /// Get AppendOnlyData if it's newer than the known indices.
+    GetADataIfNewer(Result<IfModified<AData>>),
+    //
+    // ===== Subscriptions =====
+    //
+    /// Return a success or failure status for a subscribe or unsubscribe operation.
+    Subscription(Result<()>),
+    //
+    // ===== MData history =====
+    //
+    /// Get the revisions of a sequenced MutableData entry.
+    GetMDataEntryHistory(Result<Vec<MDataRevision>>),
+    /// Get the value of a sequenced MutableData entry at a version.
+    GetMDataValueAt(Result<MDataSeqValue>),
+}
+
+/// The variant of a `Response`, without its payload.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Kind {
+    GetIData,
+    GetMData,
+    GetMDataShell,
+    GetMDataVersion,
+    ListMDataEntries,
+    ListMDataKeys,
+    ListMDataValues,
+    ListMDataUserPermissions,
+    ListMDataPermissions,
+    GetMDataValue,
+    GetAData,
+    GetADataShell,
+    GetADataOwners,
+    GetADataRange,
+    GetADataValue,
+    GetADataIndices,
+    GetADataLastEntry,
+    GetADataPermissions,
+    GetPubADataUserPermissions,
+    GetUnpubADataUserPermissions,
+    GetBalance,
+    Transaction,
+    GetLoginPacket,
+    ListAuthKeysAndVersion,
+    Mutation,
+    ListMDataEntriesPage,
+    ListMDataKeysPage,
+    ListMDataValuesPage,
+    GetADataRangePage,
+    Batch,
+    GetMDataIfNewer,
+    GetADataIfNewer,
+    Subscription,
+    GetMDataEntryHistory,
+    GetMDataValueAt,
+}
+
+impl Response {
+    /// Returns the variant of this response.
+    pub(crate) fn kind(&self) -> Kind {
+        use Response::*;
+
+        match self {
+            GetIData(_) => Kind::GetIData,
+            GetMData(_) => Kind::GetMData,
+            GetMDataShell(_) => Kind::GetMDataShell,
+            GetMDataVersion(_) => Kind::GetMDataVersion,
+            ListMDataEntries(_) => Kind::ListMDataEntries,
+            ListMDataKeys(_) => Kind::ListMDataKeys,
+            ListMDataValues(_) => Kind::ListMDataValues,
+            ListMDataUserPermissions(_) => Kind::ListMDataUserPermissions,
+            ListMDataPermissions(_) => Kind::ListMDataPermissions,
+            GetMDataValue(_) => Kind::GetMDataValue,
+            GetAData(_) => Kind::GetAData,
+            GetADataShell(_) => Kind::GetADataShell,
+            GetADataOwners(_) => Kind::GetADataOwners,
+            GetADataRange(_) => Kind::GetADataRange,
+            GetADataValue(_) => Kind::GetADataValue,
+            GetADataIndices(_) => Kind::GetADataIndices,
+            GetADataLastEntry(_) => Kind::GetADataLastEntry,
+            GetADataPermissions(_) => Kind::GetADataPermissions,
+            GetPubADataUserPermissions(_) => Kind::GetPubADataUserPermissions,
+            GetUnpubADataUserPermissions(_) => Kind::GetUnpubADataUserPermissions,
+            GetBalance(_) => Kind::GetBalance,
+            Transaction(_) => Kind::Transaction,
+            GetLoginPacket(_) => Kind::GetLoginPacket,
+            ListAuthKeysAndVersion(_) => Kind::ListAuthKeysAndVersion,
+            Mutation(_) => Kind::Mutation,
+            ListMDataEntriesPage(_) => Kind::ListMDataEntriesPage,
+            ListMDataKeysPage(_) => Kind::ListMDataKeysPage,
+            ListMDataValuesPage(_) => Kind::ListMDataValuesPage,
+            GetADataRangePage(_) => Kind::GetADataRangePage,
+            Batch(_) => Kind::Batch,
+            GetMDataIfNewer(_) => Kind::GetMDataIfNewer,
+            GetADataIfNewer(_) => Kind::GetADataIfNewer,
+            Subscription(_) => Kind::Subscription,
+            GetMDataEntryHistory(_) => Kind::GetMDataEntryHistory,
+            GetMDataValueAt(_) => Kind::GetMDataValueAt,
+        }
+    }
+
+    /// Checks that any data in this response is the data `request` asked for: ImmutableData must
+    /// hash to the requested name, and MutableData and AppendOnlyData must carry the requested
+    /// address. The responses in a batch are checked against their respective requests.
+    ///
+    /// Returns `Err(Error::UnexpectedResponse)` if this isn't a response to `request` at all, or
+    /// `Err(Error::IntegrityCheckFailed)` if the data doesn't match.
+    pub fn verify_for(&self, request: &Request) -> Result<()> {
+        if !request.expects(self) {
+            return Err(Error::UnexpectedResponse);
+        }
+
+        match (request, self) {
+            (Request::GetIData(address), Response::GetIData(Ok(data))) => {
+                data.verify_against(address)
+            }
+            (Request::GetMData(address), Response::GetMData(Ok(data)))
+            | (Request::GetMDataShell(address), Response::GetMDataShell(Ok(data)))
+            | (
+                Request::GetMDataIfNewer { address, .. },
+                Response::GetMDataIfNewer(Ok(IfModified::Modified(data))),
+            ) => verify_address(data.address(), address),
+            (Request::GetAData(address), Response::GetAData(Ok(data)))
+            | (Request::GetADataShell { address, .. }, Response::GetADataShell(Ok(data)))
+            | (
+                Request::GetADataIfNewer { address, .. },
+                Response::GetADataIfNewer(Ok(IfModified::Modified(data))),
+            ) => verify_address(data.address(), address),
+            (Request::Batch { requests, .. }, Response::Batch(responses)) => requests
+                .iter()
+                .zip(responses)
+                .try_for_each(|(request, response)| response.verify_for(request)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn verify_address<T: PartialEq>(actual: &T, requested: &T) -> Result<()> {
+    if actual == requested {
+        Ok(())
+    } else {
+        Err(Error::IntegrityCheckFailed)
+    }
+}
+
+/// Outcome of a conditional get.
+#[derive(Hash, Eq, PartialEq, PartialOrd, Ord, Clone, Serialize, Deserialize, Debug)]
+pub enum IfModified<T> {
+    /// The data has changed since the known version, and this is its current state.
+    Modified(T),
+    /// The data hasn't changed since the known version.
+    NotModified,
+}
+
+impl<T> IfModified<T> {
+    /// Returns true if the data has changed.
+    pub fn is_modified(&self) -> bool {
+        match *self {
+            IfModified::Modified(_) => true,
+            IfModified::NotModified => false,
+        }
+    }
+
+    /// Returns the current state of the data if it has changed.
+    pub fn modified(self) -> Option<T> {
+        match self {
+            IfModified::Modified(data) => Some(data),
+            IfModified::NotModified => None,
+        }
+    }
 }
 
 /// Error type for an attempted conversion from `Response` to a type implementing
@@ -150,6 +340,14 @@ try_from!(
 );
 try_from!((Vec<u8>, Signature), GetLoginPacket);
 try_from!((), Mutation);
+try_from!(MDataPage<MDataEntries>, ListMDataEntriesPage);
+try_from!(MDataPage<BTreeSet<Vec<u8>>>, ListMDataKeysPage);
+try_from!(MDataPage<MDataValues>, ListMDataValuesPage);
+try_from!(ADataPage, GetADataRangePage);
+try_from!(IfModified<MData>, GetMDataIfNewer);
+try_from!(IfModified<AData>, GetADataIfNewer);
+try_from!(Vec<MDataRevision>, GetMDataEntryHistory);
+try_from!(MDataSeqValue, GetMDataValueAt);
 
 impl fmt::Debug for Response {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -208,6 +406,31 @@ impl fmt::Debug for Response {
             }
             // Mutation
             Mutation(res) => write!(f, "Response::Mutation({:?})", ErrorDebug(res)),
+            // Paginated listings
+            ListMDataEntriesPage(res) => {
+                write!(f, "Response::ListMDataEntriesPage({:?})", ErrorDebug(res))
+            }
+            ListMDataKeysPage(res) => {
+                write!(f, "Response::ListMDataKeysPage({:?})", ErrorDebug(res))
+            }
+            ListMDataValuesPage(res) => {
+                write!(f, "Response::ListMDataValuesPage({:?})", ErrorDebug(res))
+            }
+            GetADataRangePage(res) => {
+                write!(f, "Response::GetADataRangePage({:?})", ErrorDebug(res))
+            }
+            // Batch
+            Batch(responses) => write!(f, "Response::Batch({:?})", responses),
+            // Conditional gets
+            GetMDataIfNewer(res) => write!(f, "Response::GetMDataIfNewer({:?})", ErrorDebug(res)),
+            GetADataIfNewer(res) => write!(f, "Response::GetADataIfNewer({:?})", ErrorDebug(res)),
+            // Subscriptions
+            Subscription(res) => write!(f, "Response::Subscription({:?})", ErrorDebug(res)),
+            // MData history
+            GetMDataEntryHistory(res) => {
+                write!(f, "Response::GetMDataEntryHistory({:?})", ErrorDebug(res))
+            }
+            GetMDataValueAt(res) => write!(f, "Response::GetMDataValueAt({:?})", ErrorDebug(res)),
         }
     }
 }
@@ -215,7 +438,10 @@ impl fmt::Debug for Response {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{PubImmutableData, UnseqMutableData};
+    use crate::{
+        BatchMode, MDataAddress, PubImmutableData, PubSeqAppendOnlyData, SeqMutableData,
+        UnseqMutableData, XorName,
+    };
     use std::convert::{TryFrom, TryInto};
     use unwrap::{unwrap, unwrap_err};
 
@@ -266,5 +492,84 @@ mod tests {
             TryFromError::WrongType,
             unwrap_err!(MData::try_from(Mutation(Ok(()))))
         );
+
+        let if_modified = IfModified::Modified(m_data.clone());
+        assert_eq!(
+            if_modified,
+            unwrap!(GetMDataIfNewer(Ok(if_modified.clone())).try_into())
+        );
+        assert_eq!(
+            IfModified::NotModified,
+            unwrap!(IfModified::<MData>::try_from(GetMDataIfNewer(Ok(
+                IfModified::NotModified
+            ))))
+        );
+        assert_eq!(
+            TryFromError::WrongType,
+            unwrap_err!(IfModified::<MData>::try_from(GetMData(Ok(m_data))))
+        );
+    }
+
+    #[test]
+    fn verify_for() {
+        let owner = PublicKey::Bls(threshold_crypto::SecretKey::random().public_key());
+        let idata = IData::from(PubImmutableData::new(vec![1, 2, 3]));
+        let other_idata = IData::from(PubImmutableData::new(vec![4, 5, 6]));
+        let mdata = MData::from(SeqMutableData::new(XorName([1; 32]), 15000, owner));
+        let other_mdata = MData::from(SeqMutableData::new(XorName([1; 32]), 15001, owner));
+        let adata = AData::from(PubSeqAppendOnlyData::new(XorName([2; 32]), 10000));
+
+        let request = Request::GetIData(*idata.address());
+        unwrap!(Response::GetIData(Ok(idata.clone())).verify_for(&request));
+        unwrap!(Response::GetIData(Err(Error::NoSuchData)).verify_for(&request));
+        assert_eq!(
+            Response::GetIData(Ok(other_idata)).verify_for(&request),
+            Err(Error::IntegrityCheckFailed)
+        );
+        assert_eq!(
+            Response::GetMData(Ok(mdata.clone())).verify_for(&request),
+            Err(Error::UnexpectedResponse)
+        );
+
+        let request = Request::GetMDataIfNewer {
+            address: *mdata.address(),
+            known_version: 0,
+        };
+        unwrap!(
+            Response::GetMDataIfNewer(Ok(IfModified::Modified(mdata.clone()))).verify_for(&request)
+        );
+        unwrap!(Response::GetMDataIfNewer(Ok(IfModified::NotModified)).verify_for(&request));
+        assert_eq!(
+            Response::GetMDataIfNewer(Ok(IfModified::Modified(other_mdata.clone())))
+                .verify_for(&request),
+            Err(Error::IntegrityCheckFailed)
+        );
+
+        let request = Request::GetAData(*adata.address());
+        unwrap!(Response::GetAData(Ok(adata.clone())).verify_for(&request));
+
+        let request = Request::Batch {
+            requests: vec![
+                Request::GetAData(*adata.address()),
+                Request::GetMDataShell(MDataAddress::Seq {
+                    name: XorName([1; 32]),
+                    tag: 15000,
+                }),
+            ],
+            mode: BatchMode::BestEffort,
+        };
+        unwrap!(Response::Batch(vec![
+            Response::GetAData(Ok(adata.clone())),
+            Response::GetMDataShell(Ok(mdata)),
+        ])
+        .verify_for(&request));
+        assert_eq!(
+            Response::Batch(vec![
+                Response::GetAData(Ok(adata)),
+                Response::GetMDataShell(Ok(other_mdata)),
+            ])
+            .verify_for(&request),
+            Err(Error::IntegrityCheckFailed)
+        );
     }
 }
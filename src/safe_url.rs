@@ -0,0 +1,470 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! `safe://` URLs.
+//!
+//! A URL names a piece of data, and optionally an entry in it:
+//!
+//! ```text
+//! safe://<name>?kind=<kind>&tag=<tag>&key=<key>&index=<index>
+//! ```
+//!
+//! * `name` is the z-base-32 encoded `XorName` of the data.
+//! * `kind` is one of `pub-idata`, `unpub-idata`, `seq-mdata`, `unseq-mdata`, `pub-seq-adata`,
+//!   `pub-unseq-adata`, `unpub-seq-adata` or `unpub-unseq-adata`. It's required.
+//! * `tag` is the type tag in decimal. It's required for MutableData and AppendOnlyData, and not
+//!   allowed for ImmutableData.
+//! * `key` is the percent-encoded key of an entry. It's optional, and not allowed for
+//!   ImmutableData.
+//! * `index` is the index of an AppendOnlyData entry: `n` counts from the start and `-n` from the
+//!   end, so `-0` is the end. It's optional, only allowed for AppendOnlyData, and can't be given
+//!   together with `key`.
+//!
+//! The parameters can be given in any order, but are always formatted in the order above.
+
+use crate::{
+    ADataAddress, ADataIndex, ADataKind, DataAddress, DataKind, Error, IDataAddress, IDataKind,
+    MDataAddress, MDataKind, Result, XorName,
+};
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+const SCHEME: &str = "safe://";
+
+/// URL of a piece of data on the network, and optionally of an entry in it.
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct SafeUrl {
+    address: DataAddress,
+    key: Option<Vec<u8>>,
+    index: Option<ADataIndex>,
+}
+
+impl SafeUrl {
+    /// Constructs a URL of the data at `address`.
+    pub fn new(address: impl Into<DataAddress>) -> Self {
+        Self {
+            address: address.into(),
+            key: None,
+            index: None,
+        }
+    }
+
+    /// Sets the key of the entry this URL refers to.
+    ///
+    /// Returns an error if the data is ImmutableData, or if an index is already set.
+    pub fn with_key(mut self, key: Vec<u8>) -> Result<Self> {
+        if let DataAddress::Immutable(_) = self.address {
+            return Err(parse_error("key is not allowed for ImmutableData"));
+        }
+        if self.index.is_some() {
+            return Err(parse_error("key and index are mutually exclusive"));
+        }
+        self.key = Some(key);
+        Ok(self)
+    }
+
+    /// Sets the index of the entry this URL refers to.
+    ///
+    /// Returns an error if the data isn't AppendOnlyData, or if a key is already set.
+    pub fn with_index(mut self, index: ADataIndex) -> Result<Self> {
+        if let DataAddress::AppendOnly(_) = self.address {
+        } else {
+            return Err(parse_error("index is only allowed for AppendOnlyData"));
+        }
+        if self.key.is_some() {
+            return Err(parse_error("key and index are mutually exclusive"));
+        }
+        self.index = Some(index);
+        Ok(self)
+    }
+
+    /// Returns the address of the data.
+    pub fn address(&self) -> DataAddress {
+        self.address
+    }
+
+    /// Returns the key of the entry, if any.
+    pub fn key(&self) -> Option<&[u8]> {
+        self.key.as_deref()
+    }
+
+    /// Returns the index of the entry, if any.
+    pub fn index(&self) -> Option<ADataIndex> {
+        self.index
+    }
+}
+
+impl From<DataAddress> for SafeUrl {
+    fn from(address: DataAddress) -> Self {
+        Self::new(address)
+    }
+}
+
+impl From<IDataAddress> for SafeUrl {
+    fn from(address: IDataAddress) -> Self {
+        Self::new(address)
+    }
+}
+
+impl From<MDataAddress> for SafeUrl {
+    fn from(address: MDataAddress) -> Self {
+        Self::new(address)
+    }
+}
+
+impl From<ADataAddress> for SafeUrl {
+    fn from(address: ADataAddress) -> Self {
+        Self::new(address)
+    }
+}
+
+impl Display for SafeUrl {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        write!(
+            formatter,
+            "{}{}?kind={}",
+            SCHEME,
+            self.address.name().encode_to_zbase32(),
+            kind_to_str(self.address.kind())
+        )?;
+        if let Some(tag) = self.address.tag() {
+            write!(formatter, "&tag={}", tag)?;
+        }
+        if let Some(ref key) = self.key {
+            write!(formatter, "&key={}", percent_encode(key))?;
+        }
+        match self.index {
+            Some(ADataIndex::FromStart(index)) => write!(formatter, "&index={}", index),
+            Some(ADataIndex::FromEnd(index)) => write!(formatter, "&index=-{}", index),
+            None => Ok(()),
+        }
+    }
+}
+
+impl FromStr for SafeUrl {
+    type Err = Error;
+
+    fn from_str(url: &str) -> Result<Self> {
+        let rest = match url.get(..SCHEME.len()) {
+            Some(scheme) if scheme.eq_ignore_ascii_case(SCHEME) => &url[SCHEME.len()..],
+            _ => return Err(parse_error("URL must start with \"safe://\"")),
+        };
+        let (encoded_name, query) = match rest.find('?') {
+            Some(pos) => (&rest[..pos], &rest[pos + 1..]),
+            None => (rest, ""),
+        };
+        if encoded_name.is_empty() {
+            return Err(parse_error("missing name"));
+        }
+        let name = XorName::decode_from_zbase32(encoded_name)
+            .map_err(|_| parse_error(format!("invalid name \"{}\"", encoded_name)))?;
+
+        let mut kind = None;
+        let mut tag = None;
+        let mut key = None;
+        let mut index = None;
+        for param in query.split('&').filter(|param| !param.is_empty()) {
+            let (param_name, value) = match param.find('=') {
+                Some(pos) => (&param[..pos], &param[pos + 1..]),
+                None => return Err(parse_error(format!("missing value for \"{}\"", param))),
+            };
+            let duplicate = match param_name {
+                "kind" => kind.replace(kind_from_str(value)?).is_some(),
+                "tag" => tag.replace(parse_u64("tag", value)?).is_some(),
+                "key" => key.replace(percent_decode(value)?).is_some(),
+                "index" => index.replace(parse_index(value)?).is_some(),
+                _ => return Err(parse_error(format!("unknown parameter \"{}\"", param_name))),
+            };
+            if duplicate {
+                return Err(parse_error(format!(
+                    "duplicate parameter \"{}\"",
+                    param_name
+                )));
+            }
+        }
+
+        let kind = kind.ok_or_else(|| parse_error("missing kind"))?;
+        let address = match kind {
+            DataKind::Immutable(kind) => {
+                if tag.is_some() {
+                    return Err(parse_error("tag is not allowed for ImmutableData"));
+                }
+                DataAddress::Immutable(IDataAddress::from_kind(kind, name))
+            }
+            DataKind::Mutable(kind) => {
+                let tag = tag.ok_or_else(|| parse_error("missing tag"))?;
+                DataAddress::Mutable(MDataAddress::from_kind(kind, name, tag))
+            }
+            DataKind::AppendOnly(kind) => {
+                let tag = tag.ok_or_else(|| parse_error("missing tag"))?;
+                DataAddress::AppendOnly(ADataAddress::from_kind(kind, name, tag))
+            }
+        };
+
+        let mut url = Self::new(address);
+        if let Some(key) = key {
+            url = url.with_key(key)?;
+        }
+        if let Some(index) = index {
+            url = url.with_index(index)?;
+        }
+        Ok(url)
+    }
+}
+
+fn parse_error<T: Into<String>>(message: T) -> Error {
+    Error::FailedToParse(message.into())
+}
+
+fn kind_to_str(kind: DataKind) -> &'static str {
+    match kind {
+        DataKind::Immutable(IDataKind::Pub) => "pub-idata",
+        DataKind::Immutable(IDataKind::Unpub) => "unpub-idata",
+        DataKind::Mutable(MDataKind::Seq) => "seq-mdata",
+        DataKind::Mutable(MDataKind::Unseq) => "unseq-mdata",
+        DataKind::AppendOnly(ADataKind::PubSeq) => "pub-seq-adata",
+        DataKind::AppendOnly(ADataKind::PubUnseq) => "pub-unseq-adata",
+        DataKind::AppendOnly(ADataKind::UnpubSeq) => "unpub-seq-adata",
+        DataKind::AppendOnly(ADataKind::UnpubUnseq) => "unpub-unseq-adata",
+    }
+}
+
+fn kind_from_str(kind: &str) -> Result<DataKind> {
+    Ok(match kind {
+        "pub-idata" => DataKind::Immutable(IDataKind::Pub),
+        "unpub-idata" => DataKind::Immutable(IDataKind::Unpub),
+        "seq-mdata" => DataKind::Mutable(MDataKind::Seq),
+        "unseq-mdata" => DataKind::Mutable(MDataKind::Unseq),
+        "pub-seq-adata" => DataKind::AppendOnly(ADataKind::PubSeq),
+        "pub-unseq-adata" => DataKind::AppendOnly(ADataKind::PubUnseq),
+        "unpub-seq-adata" => DataKind::AppendOnly(ADataKind::UnpubSeq),
+        "unpub-unseq-adata" => DataKind::AppendOnly(ADataKind::UnpubUnseq),
+        _ => return Err(parse_error(format!("unknown kind \"{}\"", kind))),
+    })
+}
+
+fn parse_u64(param_name: &str, value: &str) -> Result<u64> {
+    // `u64::from_str` accepts a leading '+', which isn't part of the grammar.
+    if value.is_empty() || !value.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(parse_error(format!(
+            "invalid {} \"{}\": expected a decimal number",
+            param_name, value
+        )));
+    }
+    value.parse().map_err(|_| {
+        parse_error(format!(
+            "invalid {} \"{}\": out of range",
+            param_name, value
+        ))
+    })
+}
+
+fn parse_index(value: &str) -> Result<ADataIndex> {
+    match value.strip_prefix('-') {
+        Some(value) => Ok(ADataIndex::FromEnd(parse_u64("index", value)?)),
+        None => Ok(ADataIndex::FromStart(parse_u64("index", value)?)),
+    }
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'.' || byte == b'_' || byte == b'~'
+}
+
+fn percent_encode(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        if is_unreserved(byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+fn percent_decode(encoded: &str) -> Result<Vec<u8>> {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let byte = bytes[pos];
+        if byte == b'%' {
+            // `from_str_radix` alone would also accept a sign, as in "%+1".
+            let escape = encoded
+                .get(pos + 1..pos + 3)
+                .filter(|hex| hex.bytes().all(|digit| digit.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or_else(|| {
+                    parse_error(format!("invalid percent-encoding in key \"{}\"", encoded))
+                })?;
+            decoded.push(escape);
+            pos += 3;
+        } else if is_unreserved(byte) {
+            decoded.push(byte);
+            pos += 1;
+        } else {
+            return Err(parse_error(format!(
+                "invalid character '{}' in key \"{}\"",
+                char::from(byte),
+                encoded
+            )));
+        }
+    }
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unwrap::{unwrap, unwrap_err};
+
+    fn parse_err(url: &str) -> String {
+        match unwrap_err!(url.parse::<SafeUrl>()) {
+            Error::FailedToParse(message) => message,
+            error => panic!("Unexpected error: {:?}", error),
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let name = XorName(rand::random());
+        let urls = vec![
+            SafeUrl::new(IDataAddress::Pub(name)),
+            SafeUrl::new(IDataAddress::Unpub(name)),
+            SafeUrl::new(MDataAddress::Seq { name, tag: 15000 }),
+            unwrap!(SafeUrl::new(MDataAddress::Unseq { name, tag: 0 })
+                .with_key(b"key with spaces/and%".to_vec())),
+            unwrap!(SafeUrl::new(ADataAddress::PubSeq { name, tag: 1 })
+                .with_index(ADataIndex::FromStart(7))),
+            unwrap!(SafeUrl::new(ADataAddress::PubUnseq { name, tag: 1 })
+                .with_index(ADataIndex::FromEnd(0))),
+            unwrap!(SafeUrl::new(ADataAddress::UnpubSeq { name, tag: 1 }).with_key(vec![0, 255])),
+            SafeUrl::new(ADataAddress::UnpubUnseq {
+                name,
+                tag: u64::MAX,
+            }),
+        ];
+
+        for url in urls {
+            let formatted = url.to_string();
+            assert_eq!(unwrap!(formatted.parse::<SafeUrl>()), url, "{}", formatted);
+        }
+    }
+
+    #[test]
+    fn format() {
+        let name = XorName([0; 32]);
+        let encoded_name = name.encode_to_zbase32();
+
+        let url =
+            unwrap!(SafeUrl::new(MDataAddress::Seq { name, tag: 15000 }).with_key(b"a b".to_vec()));
+        assert_eq!(
+            url.to_string(),
+            format!("safe://{}?kind=seq-mdata&tag=15000&key=a%20b", encoded_name)
+        );
+
+        let url =
+            unwrap!(SafeUrl::new(ADataAddress::PubSeq { name, tag: 1 })
+                .with_index(ADataIndex::FromEnd(3)));
+        assert_eq!(
+            url.to_string(),
+            format!("safe://{}?kind=pub-seq-adata&tag=1&index=-3", encoded_name)
+        );
+
+        // Parameters may be in any order.
+        let parsed: SafeUrl =
+            unwrap!(format!("safe://{}?index=-3&tag=1&kind=pub-seq-adata", encoded_name).parse());
+        assert_eq!(parsed, url);
+        assert_eq!(parsed.index(), Some(ADataIndex::FromEnd(3)));
+        assert_eq!(
+            parsed.address(),
+            DataAddress::AppendOnly(ADataAddress::PubSeq { name, tag: 1 })
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        let name = XorName([0; 32]).encode_to_zbase32();
+
+        assert_eq!(
+            parse_err("http://abc?kind=pub-idata"),
+            "URL must start with \"safe://\""
+        );
+        assert_eq!(parse_err("safe://?kind=pub-idata"), "missing name");
+        assert_eq!(
+            parse_err("safe://not-a-name?kind=pub-idata"),
+            "invalid name \"not-a-name\""
+        );
+        assert_eq!(parse_err(&format!("safe://{}", name)), "missing kind");
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=mdata", name)),
+            "unknown kind \"mdata\""
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=seq-mdata", name)),
+            "missing tag"
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=pub-idata&tag=1", name)),
+            "tag is not allowed for ImmutableData"
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=seq-mdata&tag=+1", name)),
+            "invalid tag \"+1\": expected a decimal number"
+        );
+        assert_eq!(
+            parse_err(&format!(
+                "safe://{}?kind=seq-mdata&tag=18446744073709551616",
+                name
+            )),
+            "invalid tag \"18446744073709551616\": out of range"
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=seq-mdata&tag=1&tag=2", name)),
+            "duplicate parameter \"tag\""
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=seq-mdata&tag=1&version=2", name)),
+            "unknown parameter \"version\""
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=seq-mdata&tag", name)),
+            "missing value for \"tag\""
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=pub-idata&key=a", name)),
+            "key is not allowed for ImmutableData"
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=seq-mdata&tag=1&index=0", name)),
+            "index is only allowed for AppendOnlyData"
+        );
+        assert_eq!(
+            parse_err(&format!(
+                "safe://{}?kind=pub-seq-adata&tag=1&index=0&key=a",
+                name
+            )),
+            "key and index are mutually exclusive"
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=seq-mdata&tag=1&key=a%2", name)),
+            "invalid percent-encoding in key \"a%2\""
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=seq-mdata&tag=1&key=%+1", name)),
+            "invalid percent-encoding in key \"%+1\""
+        );
+        assert_eq!(
+            parse_err(&format!("safe://{}?kind=seq-mdata&tag=1&key=a/b", name)),
+            "invalid character '/' in key \"a/b\""
+        );
+    }
+}
@@ -8,7 +8,6 @@
 // Software.
 
 use crate::{Error, MessageId, PublicKey, Request, Result, Signature};
-use bincode;
 use multibase::{self, Base, Decodable};
 use serde::{de::DeserializeOwned, Serialize};
 use unwrap::unwrap;
@@ -29,6 +28,11 @@ pub(crate) fn serialise<T: Serialize>(data: &T) -> Vec<u8> {
     unwrap!(bincode::serialize(data))
 }
 
+/// Wrapper for raw bincode::serialized_size.
+pub(crate) fn serialised_size<T: Serialize>(data: &T) -> u64 {
+    unwrap!(bincode::serialized_size(data))
+}
+
 /// Wrapper for z-Base-32 multibase::encode.
 pub(crate) fn encode<T: Serialize>(data: &T) -> String {
     let serialised = serialise(&data);
@@ -45,5 +49,5 @@ pub(crate) fn decode<I: Decodable, O: DeserializeOwned>(encoded: I) -> Result<O>
             base
         )));
     }
-    Ok(bincode::deserialize(&decoded).map_err(|e| Error::FailedToParse(e.to_string()))?)
+    bincode::deserialize(&decoded).map_err(|e| Error::FailedToParse(e.to_string()))
 }
@@ -0,0 +1,210 @@
+// Copyright 2019 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under the MIT license <LICENSE-MIT
+// https://opensource.org/licenses/MIT> or the Modified BSD license <LICENSE-BSD
+// https://opensource.org/licenses/BSD-3-Clause>, at your option. This file may not be copied,
+// modified, or distributed except according to those terms. Please review the Licences for the
+// specific language governing permissions and limitations relating to use of the SAFE Network
+// Software.
+
+//! Framed wire format for `Message`.
+//!
+//! Every message sent over the wire is prefixed with a fixed-size header:
+//!
+//! | Field            | Size    | Description                                   |
+//! |------------------|---------|-----------------------------------------------|
+//! | magic            | 4 bytes | Always [`WIRE_MAGIC`](constant.WIRE_MAGIC.html). |
+//! | protocol version | 2 bytes | Big-endian; see [`PROTOCOL_VERSION`](constant.PROTOCOL_VERSION.html). |
+//! | payload kind     | 1 byte  | Request, response or notification.            |
+//! | payload length   | 4 bytes | Big-endian length of the payload in bytes.    |
+//!
+//! The header is followed by the bincode-serialised `Message`. The header layout itself never
+//! depends on bincode, so peers running different versions of this crate can always read it and
+//! reject a message they don't understand instead of mis-decoding it.
+
+use crate::{utils, Error, Message, Result};
+
+/// Magic bytes at the start of every framed `Message`.
+pub const WIRE_MAGIC: [u8; 4] = *b"SAFE";
+
+/// Version of the wire protocol produced by this crate.
+///
+/// This must be increased whenever the serialised layout of `Message` changes.
+pub const PROTOCOL_VERSION: u16 = 2;
+
+const HEADER_LEN: usize = 11;
+
+/// Kind of payload carried in a framed message.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum PayloadKind {
+    Request = 0,
+    Response = 1,
+    Notification = 2,
+}
+
+impl PayloadKind {
+    fn of(message: &Message) -> Self {
+        match message {
+            Message::Request { .. } => PayloadKind::Request,
+            Message::Response { .. } => PayloadKind::Response,
+            Message::Notification { .. } => PayloadKind::Notification,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self> {
+        match byte {
+            0 => Ok(PayloadKind::Request),
+            1 => Ok(PayloadKind::Response),
+            2 => Ok(PayloadKind::Notification),
+            _ => Err(Error::FailedToParse(format!(
+                "Unknown payload kind {}",
+                byte
+            ))),
+        }
+    }
+}
+
+impl Message {
+    /// Serialises the message, prefixed with the wire header.
+    ///
+    /// Returns `Err(Error::ExceededSize)` if the serialised message doesn't fit the header's length
+    /// field.
+    pub fn to_wire_bytes(&self) -> Result<Vec<u8>> {
+        let payload = utils::serialise(self);
+        if payload.len() > u32::MAX as usize {
+            return Err(Error::ExceededSize);
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+        bytes.extend_from_slice(&WIRE_MAGIC);
+        bytes.extend_from_slice(&PROTOCOL_VERSION.to_be_bytes());
+        bytes.push(PayloadKind::of(self) as u8);
+        bytes.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    /// Parses a message produced by `to_wire_bytes`.
+    ///
+    /// Returns `Err(Error::UnsupportedProtocolVersion)` if the message was produced with a
+    /// different protocol version, `Err(Error::FailedToParse)` if it is malformed, and
+    /// `Err(Error::InvalidOperation)` if it is a request which fails `Request::validate`.
+    pub fn from_wire_bytes(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() < HEADER_LEN {
+            return Err(Error::FailedToParse(
+                "Message is shorter than the wire header".to_string(),
+            ));
+        }
+        if bytes[..4] != WIRE_MAGIC {
+            return Err(Error::FailedToParse(
+                "Message doesn't start with the wire magic bytes".to_string(),
+            ));
+        }
+
+        let version = u16::from_be_bytes([bytes[4], bytes[5]]);
+        if version != PROTOCOL_VERSION {
+            return Err(Error::UnsupportedProtocolVersion(version));
+        }
+
+        let kind = PayloadKind::from_byte(bytes[6])?;
+        let len = u32::from_be_bytes([bytes[7], bytes[8], bytes[9], bytes[10]]) as usize;
+        let payload = &bytes[HEADER_LEN..];
+        if payload.len() != len {
+            return Err(Error::FailedToParse(format!(
+                "Expected a payload of {} bytes, but got {}",
+                len,
+                payload.len()
+            )));
+        }
+
+        let message: Message =
+            bincode::deserialize(payload).map_err(|e| Error::FailedToParse(e.to_string()))?;
+        if PayloadKind::of(&message) != kind {
+            return Err(Error::FailedToParse(format!(
+                "Payload kind {:?} doesn't match the header",
+                PayloadKind::of(&message)
+            )));
+        }
+        if let Message::Request { ref request, .. } = message {
+            request.validate()?;
+        }
+
+        Ok(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Coins, MessageId, Notification, Request, Response, Transaction};
+    use unwrap::{unwrap, unwrap_err};
+
+    fn gen_messages() -> Vec<Message> {
+        vec![
+            Message::Request {
+                request: Request::GetBalance,
+                message_id: MessageId::new(),
+                signature: None,
+                approvals: None,
+            },
+            Message::Response {
+                response: Response::Mutation(Ok(())),
+                message_id: MessageId::new(),
+            },
+            Message::Notification {
+                notification: Notification::Transaction(Transaction {
+                    id: 1,
+                    amount: Coins::from_nano(2),
+                }),
+            },
+        ]
+    }
+
+    #[test]
+    fn round_trip() {
+        for message in gen_messages() {
+            let bytes = unwrap!(message.to_wire_bytes());
+            assert_eq!(bytes[..4], WIRE_MAGIC);
+            assert_eq!(unwrap!(Message::from_wire_bytes(&bytes)), message);
+        }
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let mut bytes = unwrap!(gen_messages()[0].to_wire_bytes());
+        bytes[4..6].copy_from_slice(&(PROTOCOL_VERSION + 1).to_be_bytes());
+        assert_eq!(
+            unwrap_err!(Message::from_wire_bytes(&bytes)),
+            Error::UnsupportedProtocolVersion(PROTOCOL_VERSION + 1)
+        );
+    }
+
+    #[test]
+    fn malformed() {
+        let bytes = unwrap!(gen_messages()[1].to_wire_bytes());
+
+        // Bad magic.
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        match Message::from_wire_bytes(&bad_magic) {
+            Err(Error::FailedToParse(_)) => (),
+            _ => panic!("Unexpected result"),
+        }
+
+        // Truncated header and payload.
+        for len in &[HEADER_LEN - 1, bytes.len() - 1] {
+            match Message::from_wire_bytes(&bytes[..*len]) {
+                Err(Error::FailedToParse(_)) => (),
+                _ => panic!("Unexpected result"),
+            }
+        }
+
+        // Payload kind doesn't match the payload.
+        let mut bad_kind = bytes;
+        bad_kind[6] = PayloadKind::Request as u8;
+        match Message::from_wire_bytes(&bad_kind) {
+            Err(Error::FailedToParse(_)) => (),
+            _ => panic!("Unexpected result"),
+        }
+    }
+}